@@ -0,0 +1,69 @@
+use crate::{Commit, PollMeta};
+use tokio::sync::oneshot;
+
+// A payload delivered by `start_acked`. The poller waits for `ack` before it advances its resume
+// cursor and persists its state; dropping `ack` instead makes it request the same data again.
+#[derive(Debug)]
+pub struct Acked<T> {
+    pub data: T,
+    pub meta: PollMeta,
+    pub ack: Ack,
+}
+
+#[derive(Debug)]
+pub struct Ack(oneshot::Sender<()>);
+
+impl Ack {
+    pub(crate) fn pair() -> (Self, oneshot::Receiver<()>) {
+        let (sender, receiver) = oneshot::channel();
+        (Self(sender), receiver)
+    }
+
+    // Call once the payload has been processed (or durably handed off).
+    pub fn ack(self) {
+        let _ = self.0.send(());
+    }
+}
+
+// What a delivery callback tells the poll loop: whether the poll's cursor and state may be
+// committed. Plain callbacks return `()`, which always commits.
+pub(crate) trait Settled {
+    fn committed(&self) -> bool;
+
+    // Payloads handed to the delivery queue have not been handled yet; their state goes along to
+    // the consumer instead of being committed by the poll loop.
+    fn hand_over(self, commit: Commit) -> Option<Commit>
+    where
+        Self: Sized,
+    {
+        Some(commit)
+    }
+}
+
+impl Settled for () {
+    fn committed(&self) -> bool {
+        true
+    }
+}
+
+impl Settled for bool {
+    fn committed(&self) -> bool {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dropped_ack_is_not_committed() {
+        let (ack, receiver) = Ack::pair();
+        ack.ack();
+        assert!(receiver.await.is_ok());
+
+        let (ack, receiver) = Ack::pair();
+        drop(ack);
+        assert!(receiver.await.is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use serde_json::Value;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub(crate) type AlertHandler = Arc<dyn Fn(&Alert) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Condition {
+    Above(f64),
+    Below(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pointer: String,
+    condition: Condition,
+    polls: u32,
+}
+
+impl AlertRule {
+    pub fn above(pointer: impl Into<String>, threshold: f64) -> Self {
+        Self::new(pointer.into(), Condition::Above(threshold))
+    }
+
+    pub fn below(pointer: impl Into<String>, threshold: f64) -> Self {
+        Self::new(pointer.into(), Condition::Below(threshold))
+    }
+
+    fn new(pointer: String, condition: Condition) -> Self {
+        Self {
+            pointer,
+            condition,
+            polls: 1,
+        }
+    }
+
+    // Consecutive polls the condition has to hold before the alert fires (default: 1).
+    pub fn for_polls(mut self, polls: u32) -> Self {
+        self.polls = polls;
+        self
+    }
+
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    fn breached(&self, value: f64) -> bool {
+        match self.condition {
+            Condition::Above(threshold) => value > threshold,
+            Condition::Below(threshold) => value < threshold,
+        }
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.condition {
+            Condition::Above(threshold) => write!(f, "{} > {threshold}", self.pointer)?,
+            Condition::Below(threshold) => write!(f, "{} < {threshold}", self.pointer)?,
+        }
+        if self.polls > 1 {
+            write!(f, " for {} polls", self.polls)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Alert {
+    pub rule: AlertRule,
+    pub value: f64,
+    // Since the first poll of the current breach.
+    pub duration: Duration,
+    pub polls: u32,
+}
+
+struct Streak {
+    polls: u32,
+    since: Instant,
+}
+
+// Each rule fires once per breach and re-arms when a poll no longer matches it.
+pub(crate) struct Alerts<T> {
+    to_value: fn(&T) -> Option<Value>,
+    rules: Vec<(AlertRule, Mutex<Option<Streak>>)>,
+}
+
+impl<T> Alerts<T> {
+    pub(crate) fn new() -> Self
+    where
+        T: serde::Serialize,
+    {
+        Self {
+            to_value: |data| serde_json::to_value(data).ok(),
+            rules: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, rule: AlertRule) {
+        self.rules.push((rule, Mutex::new(None)));
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        for (rule, _) in &self.rules {
+            if !rule.pointer.starts_with('/') {
+                return Err(format!("`{}` is not a JSON pointer", rule.pointer));
+            }
+            if rule.polls == 0 {
+                return Err(format!("alert `{rule}` needs at least one poll"));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn observe(&self, now: Instant, data: &T) -> Vec<Alert> {
+        let Some(value) = (self.to_value)(data) else {
+            return Vec::new();
+        };
+        let mut fired = Vec::new();
+        for (rule, streak) in &self.rules {
+            let mut streak = streak.lock().unwrap();
+            let Some(sample) = value
+                .pointer(&rule.pointer)
+                .and_then(Value::as_f64)
+                .filter(|&sample| rule.breached(sample))
+            else {
+                *streak = None;
+                continue;
+            };
+            let streak = streak.get_or_insert(Streak {
+                polls: 0,
+                since: now,
+            });
+            streak.polls += 1;
+            if streak.polls == rule.polls {
+                fired.push(Alert {
+                    rule: rule.clone(),
+                    value: sample,
+                    duration: now.saturating_duration_since(streak.since),
+                    polls: streak.polls,
+                });
+            }
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fires_once_after_consecutive_breaches() {
+        let mut alerts = Alerts::<Value>::new();
+        alerts.add(AlertRule::above("/queue/depth", 1000.0).for_polls(3));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let depth = |depth: u64| json!({"queue": {"depth": depth}});
+
+        assert!(alerts.observe(at(0), &depth(1500)).is_empty());
+        assert!(alerts.observe(at(1), &depth(900)).is_empty());
+        assert!(alerts.observe(at(2), &depth(1200)).is_empty());
+        assert!(alerts.observe(at(3), &depth(1300)).is_empty());
+
+        let fired = alerts.observe(at(4), &depth(1400));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].value, 1400.0);
+        assert_eq!(fired[0].duration, Duration::from_secs(2));
+        assert_eq!(fired[0].rule.to_string(), "/queue/depth > 1000 for 3 polls");
+
+        assert!(alerts.observe(at(5), &depth(1500)).is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+use json_poller::{JsonPoller, JsonPollerBuilder};
+use serde_json::Value;
+use std::io::Write;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: json-poller [OPTIONS] <URL>
+
+Polls URL and prints every payload on its own line.
+
+Options:
+      --interval-ms <MS>         Poll interval (default: 500)
+      --timeout-ms <MS>          Request timeout (default: 1000)
+      --connect-timeout-ms <MS>  Connect timeout
+      --retries <N>              Retries for retryable status codes
+      --retry-delay-ms <MS>      Delay before the first retry
+      --max-backoff-ms <MS>      Upper bound for backoff between failed polls
+  -H, --header <NAME:VALUE>      Extra request header, may be repeated
+  -q, --query <KEY=VALUE>        Extra query parameter, may be repeated
+  -f, --field <PATH>             Print only this field (`.data.items[0]` or `/data/items/0`)
+  -p, --pretty                   Pretty-print payloads
+      --once                     Fetch a single payload and exit
+  -h, --help                     Print this help
+";
+
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    url: String,
+    interval_ms: Option<u64>,
+    timeout_ms: Option<u64>,
+    connect_timeout_ms: Option<u64>,
+    retries: Option<u32>,
+    retry_delay_ms: Option<u64>,
+    max_backoff_ms: Option<u64>,
+    headers: Vec<(String, String)>,
+    query: Vec<(String, String)>,
+    field: Option<String>,
+    pretty: bool,
+    once: bool,
+}
+
+enum Parsed {
+    Run(Args),
+    Help,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Parsed, String> {
+    let mut parsed = Args::default();
+    let mut url = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} requires a value"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "--interval-ms" => parsed.interval_ms = Some(number(&arg, value(&arg)?)?),
+            "--timeout-ms" => parsed.timeout_ms = Some(number(&arg, value(&arg)?)?),
+            "--connect-timeout-ms" => parsed.connect_timeout_ms = Some(number(&arg, value(&arg)?)?),
+            "--retries" => parsed.retries = Some(number(&arg, value(&arg)?)?),
+            "--retry-delay-ms" => parsed.retry_delay_ms = Some(number(&arg, value(&arg)?)?),
+            "--max-backoff-ms" => parsed.max_backoff_ms = Some(number(&arg, value(&arg)?)?),
+            "-H" | "--header" => parsed.headers.push(pair(&value(&arg)?, ':')?),
+            "-q" | "--query" => parsed.query.push(pair(&value(&arg)?, '=')?),
+            "-f" | "--field" => parsed.field = Some(pointer(&value(&arg)?)),
+            "-p" | "--pretty" => parsed.pretty = true,
+            "--once" => parsed.once = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option {flag}")),
+            _ if url.is_none() => url = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+    parsed.url = url.ok_or("missing URL")?;
+    Ok(Parsed::Run(parsed))
+}
+
+fn number<N: std::str::FromStr>(flag: &str, value: String) -> Result<N, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{flag} expects a number, got `{value}`"))
+}
+
+fn pair(value: &str, separator: char) -> Result<(String, String), String> {
+    let (key, value) = value
+        .split_once(separator)
+        .ok_or(format!("expected `key{separator}value`, got `{value}`"))?;
+    Ok((key.trim().to_string(), value.trim().to_string()))
+}
+
+// Accepts JSON pointers as-is and turns jq-style paths such as `.data.items[0]` into one.
+fn pointer(path: &str) -> String {
+    if path.starts_with('/') {
+        return path.to_string();
+    }
+    path.split(['.', '['])
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", segment.trim_end_matches(']')))
+        .collect()
+}
+
+fn builder(args: &Args) -> JsonPollerBuilder<Value> {
+    let mut builder = JsonPoller::builder(&args.url);
+    if let Some(ms) = args.interval_ms {
+        builder = builder.poll_interval_ms(ms);
+    }
+    if let Some(ms) = args.timeout_ms {
+        builder = builder.request_timeout_ms(ms);
+    }
+    if let Some(ms) = args.connect_timeout_ms {
+        builder = builder.connect_timeout(Duration::from_millis(ms));
+    }
+    if let Some(retries) = args.retries {
+        builder = builder.status_retries(retries);
+    }
+    if let Some(ms) = args.retry_delay_ms {
+        builder = builder.retry_delay_ms(ms);
+    }
+    if let Some(ms) = args.max_backoff_ms {
+        builder = builder.max_backoff_ms(ms);
+    }
+    for (name, value) in &args.headers {
+        builder = builder.header(name, value);
+    }
+    if !args.query.is_empty() {
+        builder = builder.query(&args.query);
+    }
+    builder
+}
+
+fn render(args: &Args, value: &Value) -> String {
+    let value = match &args.field {
+        Some(pointer) => value.pointer(pointer).unwrap_or(&Value::Null),
+        None => value,
+    };
+    if args.pretty {
+        serde_json::to_string_pretty(value).unwrap_or_default()
+    } else {
+        value.to_string()
+    }
+}
+
+fn print(line: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{line}")?;
+    stdout.flush()
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Parsed::Run(args)) => args,
+        Ok(Parsed::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let poller = match builder(&args).build() {
+        Ok(poller) => poller,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if args.once {
+        return match poller.fetch_once().await {
+            Ok(value) => match print(&render(&args, &value)) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(_) => ExitCode::FAILURE,
+            },
+            Err(e) => {
+                eprintln!("error: {e:?}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // Stops quietly when stdout is closed, e.g. when piped into `head`.
+    let result = poller
+        .start_with_results(|result, _| {
+            let printed = match result {
+                Ok(value) => print(&render(&args, &value)),
+                Err(e) => {
+                    eprintln!("error: {e:?}");
+                    Ok(())
+                }
+            };
+            async move { printed }
+        })
+        .await;
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        match parse_args(args.iter().map(|arg| arg.to_string()))? {
+            Parsed::Run(args) => Ok(args),
+            Parsed::Help => Err("help".into()),
+        }
+    }
+
+    #[test]
+    fn test_flags_are_parsed() {
+        let args = parse(&[
+            "--interval-ms",
+            "250",
+            "-H",
+            "Authorization: Bearer x",
+            "-q",
+            "symbol=EURUSD",
+            "-f",
+            ".data.items[0]",
+            "--pretty",
+            "http://example.com",
+        ])
+        .unwrap();
+
+        assert_eq!(args.url, "http://example.com");
+        assert_eq!(args.interval_ms, Some(250));
+        assert_eq!(args.headers, [("Authorization".into(), "Bearer x".into())]);
+        assert_eq!(args.query, [("symbol".into(), "EURUSD".into())]);
+        assert_eq!(args.field.as_deref(), Some("/data/items/0"));
+        assert!(args.pretty);
+        assert!(builder(&args).build().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_arguments_are_reported() {
+        assert_eq!(parse(&[]), Err("missing URL".into()));
+        assert!(parse(&["--interval-ms", "soon", "http://a"]).is_err());
+        assert!(parse(&["--verbose", "http://a"]).is_err());
+        assert!(parse(&["-H", "no-colon", "http://a"]).is_err());
+    }
+}
@@ -0,0 +1,40 @@
+use crate::JsonPollerError;
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+// An owned response body that types borrowing from it (`&str`, `Cow<str>`, `&RawValue`) can be
+// deserialized from, avoiding a String allocation per field.
+#[derive(Debug, Clone)]
+pub struct PollBody {
+    bytes: Bytes,
+    status: StatusCode,
+    headers: HeaderMap,
+}
+
+impl PollBody {
+    pub(crate) fn new(bytes: Bytes, status: StatusCode, headers: HeaderMap) -> Self {
+        Self {
+            bytes,
+            status,
+            headers,
+        }
+    }
+
+    pub fn parse<'de, U: Deserialize<'de>>(&'de self) -> Result<U, JsonPollerError> {
+        Ok(serde_json::from_slice(&self.bytes)?)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
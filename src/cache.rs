@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+// Backs `fetch_once` with the outcome of the last request. Callers are serialised behind one
+// request: its outcome is shared with every caller that was already waiting while it ran, and a
+// value (never an error) is also reused while younger than `ttl`.
+pub(crate) struct ResponseCache<T, E> {
+    ttl: Duration,
+    slot: Mutex<Option<(Result<T, E>, Instant)>>,
+    clone: fn(&T) -> T,
+}
+
+impl<T, E: Clone> ResponseCache<T, E> {
+    pub(crate) fn new(ttl: Duration) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            ttl,
+            slot: Mutex::new(None),
+            clone: T::clone,
+        }
+    }
+
+    pub(crate) async fn get_or_fetch<Fut>(&self, fetch: impl FnOnce() -> Fut) -> Result<T, E>
+    where
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let arrived = Instant::now();
+        let mut slot = self.slot.lock().await;
+        match &*slot {
+            Some((outcome, fetched)) if *fetched >= arrived => return self.share(outcome),
+            Some((Ok(value), fetched)) if fetched.elapsed() < self.ttl => {
+                return Ok((self.clone)(value))
+            }
+            _ => {}
+        }
+        let outcome = fetch().await;
+        *slot = Some((self.share(&outcome), Instant::now()));
+        outcome
+    }
+
+    fn share(&self, outcome: &Result<T, E>) -> Result<T, E> {
+        match outcome {
+            Ok(value) => Ok((self.clone)(value)),
+            Err(e) => Err(e.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_fetch() {
+        let cache = Arc::new(ResponseCache::new(Duration::ZERO));
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let callers = (0..4).map(|_| {
+            let cache = cache.clone();
+            let fetches = fetches.clone();
+            tokio::spawn(async move {
+                cache
+                    .get_or_fetch(|| async {
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        Ok::<_, ()>(fetches.fetch_add(1, Ordering::SeqCst))
+                    })
+                    .await
+                    .unwrap()
+            })
+        });
+
+        let values = futures_util::future::join_all(callers).await;
+        assert!(values.into_iter().all(|value| value.unwrap() == 0));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        let next = cache
+            .get_or_fetch(|| async { Ok::<_, ()>(9) })
+            .await
+            .unwrap();
+        assert_eq!(next, 9);
+    }
+
+    #[tokio::test]
+    async fn test_waiting_callers_share_a_failure() {
+        let cache = ResponseCache::<u32, &str>::new(Duration::from_secs(60));
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err("unavailable")
+        };
+
+        let (first, second) = tokio::join!(cache.get_or_fetch(fetch), cache.get_or_fetch(fetch));
+        assert_eq!((first, second), (Err("unavailable"), Err("unavailable")));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get_or_fetch(|| async { Ok(4) }).await, Ok(4));
+    }
+
+    #[tokio::test]
+    async fn test_value_is_reused_within_ttl() {
+        let cache = ResponseCache::new(Duration::from_millis(50));
+        assert_eq!(cache.get_or_fetch(|| async { Ok::<_, ()>(1) }).await, Ok(1));
+        assert_eq!(cache.get_or_fetch(|| async { Ok::<_, ()>(2) }).await, Ok(1));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(cache.get_or_fetch(|| async { Ok::<_, ()>(3) }).await, Ok(3));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::watermark::parse_rfc3339;
+use reqwest::tls::TlsInfo;
+use reqwest::Response;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+// The TLS peer certificate of the last response, when `certificate_info` is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateInfo {
+    pub not_after: SystemTime,
+    // Distinguished name of the issuer, e.g. `C=US, O=Let's Encrypt, CN=R3`.
+    pub issuer: String,
+}
+
+impl CertificateInfo {
+    // Time left until `not_after`; zero once the certificate has expired.
+    pub fn expires_in(&self) -> Duration {
+        self.not_after
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn from_response(response: &Response) -> Option<Self> {
+        let der = response.extensions().get::<TlsInfo>()?.peer_certificate()?;
+        Self::from_der(der)
+    }
+
+    // Reads just enough of the X.509 `TBSCertificate` to get at the issuer and validity.
+    pub(crate) fn from_der(der: &[u8]) -> Option<Self> {
+        let mut input = der;
+        let mut certificate = expect(&mut input, SEQUENCE)?;
+        let mut tbs = expect(&mut certificate, SEQUENCE)?;
+        if tbs.first() == Some(&VERSION) {
+            read(&mut tbs)?;
+        }
+        expect(&mut tbs, INTEGER)?;
+        expect(&mut tbs, SEQUENCE)?;
+        let issuer = expect(&mut tbs, SEQUENCE)?;
+        let mut validity = expect(&mut tbs, SEQUENCE)?;
+        read(&mut validity)?;
+        let (tag, not_after) = read(&mut validity)?;
+        Some(Self {
+            not_after: parse_time(tag, not_after)?,
+            issuer: distinguished_name(issuer)?,
+        })
+    }
+}
+
+// Warns once per certificate, on the first response where it expires within `before`.
+pub(crate) struct ExpiryWarning {
+    before: Duration,
+    warned: Mutex<Option<SystemTime>>,
+}
+
+impl ExpiryWarning {
+    pub(crate) fn new(before: Duration) -> Self {
+        Self {
+            before,
+            warned: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn check(&self, certificate: &CertificateInfo) -> Option<Duration> {
+        let expires_in = certificate.expires_in();
+        if expires_in >= self.before {
+            return None;
+        }
+        let mut warned = self.warned.lock().unwrap();
+        let first = *warned != Some(certificate.not_after);
+        *warned = Some(certificate.not_after);
+        first.then_some(expires_in)
+    }
+}
+
+const INTEGER: u8 = 0x02;
+const OID: u8 = 0x06;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const UTC_TIME: u8 = 0x17;
+const GENERALIZED_TIME: u8 = 0x18;
+const VERSION: u8 = 0xa0;
+
+// One DER TLV, advancing `input` past it.
+fn read<'a>(input: &mut &'a [u8]) -> Option<(u8, &'a [u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, mut rest) = rest.split_first()?;
+    let len = match first {
+        0..=0x7f => usize::from(first),
+        0x81..=0x84 => {
+            let (bytes, tail) = rest.split_at_checked(usize::from(first & 0x7f))?;
+            rest = tail;
+            bytes.iter().fold(0, |len, &b| len << 8 | usize::from(b))
+        }
+        _ => return None,
+    };
+    let (content, tail) = rest.split_at_checked(len)?;
+    *input = tail;
+    Some((tag, content))
+}
+
+fn expect<'a>(input: &mut &'a [u8], tag: u8) -> Option<&'a [u8]> {
+    let (actual, content) = read(input)?;
+    (actual == tag).then_some(content)
+}
+
+fn distinguished_name(mut name: &[u8]) -> Option<String> {
+    let mut parts = Vec::new();
+    while !name.is_empty() {
+        let mut set = expect(&mut name, SET)?;
+        while !set.is_empty() {
+            let mut attribute = expect(&mut set, SEQUENCE)?;
+            let oid = expect(&mut attribute, OID)?;
+            let (_, value) = read(&mut attribute)?;
+            let key = match oid {
+                [0x55, 0x04, 0x03] => "CN",
+                [0x55, 0x04, 0x06] => "C",
+                [0x55, 0x04, 0x07] => "L",
+                [0x55, 0x04, 0x08] => "ST",
+                [0x55, 0x04, 0x0a] => "O",
+                [0x55, 0x04, 0x0b] => "OU",
+                _ => continue,
+            };
+            parts.push(format!("{key}={}", String::from_utf8_lossy(value)));
+        }
+    }
+    Some(parts.join(", "))
+}
+
+// `YYMMDDHHMMSSZ` (years 1950-2049) or `YYYYMMDDHHMMSSZ`.
+fn parse_time(tag: u8, value: &[u8]) -> Option<SystemTime> {
+    let value = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        UTC_TIME => {
+            let (yy, rest) = value.split_at_checked(2)?;
+            let yy: u32 = yy.parse().ok()?;
+            (if yy < 50 { 2000 + yy } else { 1900 + yy }, rest)
+        }
+        GENERALIZED_TIME => {
+            let (yyyy, rest) = value.split_at_checked(4)?;
+            (yyyy.parse().ok()?, rest)
+        }
+        _ => return None,
+    };
+    if rest.len() != 10 || !rest.is_ascii() {
+        return None;
+    }
+    let field = |i: usize| &rest[i..i + 2];
+    parse_rfc3339(&format!(
+        "{year:04}-{}-{}T{}:{}:{}Z",
+        field(0),
+        field(2),
+        field(4),
+        field(6),
+        field(8)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn der(tag: u8, parts: &[&[u8]]) -> Vec<u8> {
+        let content = parts.concat();
+        let mut out = vec![tag];
+        match content.len() {
+            len @ 0..=0x7f => out.push(len as u8),
+            len => out.extend([0x82, (len >> 8) as u8, len as u8]),
+        }
+        out.extend(content);
+        out
+    }
+
+    fn attribute(oid: u8, value: &str) -> Vec<u8> {
+        let pair = der(
+            SEQUENCE,
+            &[
+                &der(OID, &[&[0x55, 0x04, oid]]),
+                &der(0x13, &[value.as_bytes()]),
+            ],
+        );
+        der(SET, &[&pair])
+    }
+
+    #[test]
+    fn test_issuer_and_expiry_are_read_from_der() {
+        let issuer = der(
+            SEQUENCE,
+            &[
+                &attribute(0x06, "US"),
+                &attribute(0x0a, "Example CA"),
+                &attribute(0x03, "R3"),
+            ],
+        );
+        let validity = der(
+            SEQUENCE,
+            &[
+                &der(UTC_TIME, &[b"240101000000Z"]),
+                &der(GENERALIZED_TIME, &[b"20240501123000Z"]),
+            ],
+        );
+        let padding = vec![0u8; 200];
+        let tbs = der(
+            SEQUENCE,
+            &[
+                &der(VERSION, &[&der(INTEGER, &[&[2]])]),
+                &der(INTEGER, &[&[0x01, 0x02]]),
+                &der(SEQUENCE, &[&der(OID, &[&[0x2a, 0x86]])]),
+                &issuer,
+                &validity,
+                &der(SEQUENCE, &[&padding]),
+            ],
+        );
+        let certificate = der(SEQUENCE, &[&tbs]);
+
+        let info = CertificateInfo::from_der(&certificate).unwrap();
+        assert_eq!(info.issuer, "C=US, O=Example CA, CN=R3");
+        assert_eq!(
+            info.not_after,
+            UNIX_EPOCH + Duration::from_secs(1_714_566_600)
+        );
+        assert_eq!(info.expires_in(), Duration::ZERO);
+        assert_eq!(CertificateInfo::from_der(&certificate[..40]), None);
+    }
+
+    #[test]
+    fn test_expiry_warning_fires_once_per_certificate() {
+        let warning = ExpiryWarning::new(Duration::from_secs(3600));
+        let soon = |secs| CertificateInfo {
+            not_after: SystemTime::now() + Duration::from_secs(secs),
+            issuer: "CN=R3".into(),
+        };
+        assert_eq!(warning.check(&soon(7200)), None);
+        let expiring = soon(600);
+        assert!(warning.check(&expiring).is_some());
+        assert_eq!(warning.check(&expiring), None);
+        assert!(warning.check(&soon(60)).is_some());
+    }
+
+    #[test]
+    fn test_utc_time_century() {
+        assert_eq!(parse_time(UTC_TIME, b"700101000000Z"), Some(UNIX_EPOCH));
+        assert_eq!(
+            parse_time(UTC_TIME, b"490101000000Z"),
+            parse_time(GENERALIZED_TIME, b"20490101000000Z")
+        );
+        assert_eq!(parse_time(UTC_TIME, b"7001010000Z"), None);
+    }
+}
@@ -0,0 +1,261 @@
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+type HashFn = Arc<dyn Fn(&[u8]) -> u64 + Send + Sync>;
+
+// The built-in hashes give the same digest for the same bytes on every build and Rust release,
+// since a `state_store` keeps digests across restarts and upgrades.
+#[derive(Clone, Default)]
+pub enum ChangeHash {
+    #[default]
+    Fnv1a,
+    #[cfg(feature = "xxhash")]
+    Xxh3,
+    #[cfg(feature = "blake3")]
+    Blake3,
+    Custom(HashFn),
+}
+
+impl ChangeHash {
+    pub fn custom<F>(hash: F) -> Self
+    where
+        F: Fn(&[u8]) -> u64 + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(hash))
+    }
+
+    fn hash(&self, bytes: &[u8]) -> u64 {
+        match self {
+            Self::Fnv1a => fnv1a(bytes),
+            #[cfg(feature = "xxhash")]
+            Self::Xxh3 => xxhash_rust::xxh3::xxh3_64(bytes),
+            // The first eight bytes of the 256-bit hash.
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => {
+                let hash = blake3::hash(bytes);
+                u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap())
+            }
+            Self::Custom(hash) => hash(bytes),
+        }
+    }
+}
+
+impl fmt::Debug for ChangeHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fnv1a => f.write_str("Fnv1a"),
+            #[cfg(feature = "xxhash")]
+            Self::Xxh3 => f.write_str("Xxh3"),
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => f.write_str("Blake3"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChangeInput {
+    #[default]
+    Raw,
+    Parsed,
+    Canonical,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChangeDetection {
+    pub(crate) enabled: bool,
+    pub(crate) hash: ChangeHash,
+    pub(crate) input: ChangeInput,
+    pub(crate) ignore: Vec<String>,
+}
+
+impl ChangeDetection {
+    // Returns 0 when nothing consumes the digest, so the default configuration pays nothing.
+    pub(crate) fn digest(&self, body: &[u8]) -> u64 {
+        if !self.enabled {
+            return 0;
+        }
+        if self.input == ChangeInput::Raw && self.ignore.is_empty() {
+            return self.hash.hash(body);
+        }
+        match serde_json::from_slice::<Value>(body) {
+            Ok(mut value) => {
+                for pointer in &self.ignore {
+                    remove_pointer(&mut value, pointer);
+                }
+                let text = match self.input {
+                    ChangeInput::Canonical => {
+                        let mut out = String::new();
+                        write_canonical(&value, &mut out);
+                        out
+                    }
+                    _ => value.to_string(),
+                };
+                self.hash.hash(text.as_bytes())
+            }
+            Err(_) => self.hash.hash(body),
+        }
+    }
+
+    pub(crate) fn combine(&self, digests: impl IntoIterator<Item = u64>) -> u64 {
+        if !self.enabled {
+            return 0;
+        }
+        let bytes: Vec<u8> = digests.into_iter().flat_map(u64::to_le_bytes).collect();
+        self.hash.hash(&bytes)
+    }
+}
+
+// Sorts object keys explicitly, since serde_json keeps insertion order when another crate in the
+// build enables `preserve_order`, and writes integral floats as integers so `1.0`, `1e0` and `1`
+// compare equal.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Number(number) => match number.as_f64() {
+            Some(float)
+                if number.is_f64() && float.fract() == 0.0 && float.abs() < i64::MAX as f64 =>
+            {
+                out.push_str(&(float as i64).to_string());
+            }
+            _ => out.push_str(&number.to_string()),
+        },
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+// Removes the value addressed by an RFC 6901 JSON Pointer, if present.
+fn remove_pointer(value: &mut Value, pointer: &str) {
+    let Some((parent, last)) = pointer.rsplit_once('/') else {
+        return;
+    };
+    let key = last.replace("~1", "/").replace("~0", "~");
+    match value.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.remove(&key);
+        }
+        Some(Value::Array(items)) => {
+            if let Ok(index) = key.parse::<usize>() {
+                if index < items.len() {
+                    items.remove(index);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(hash: ChangeHash, input: ChangeInput) -> ChangeDetection {
+        ChangeDetection {
+            enabled: true,
+            hash,
+            input,
+            ignore: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parsed_input_ignores_formatting() {
+        let raw = detection(ChangeHash::Fnv1a, ChangeInput::Raw);
+        let parsed = detection(ChangeHash::Fnv1a, ChangeInput::Parsed);
+        let compact = br#"{"a":1,"b":[2,3]}"#;
+        let pretty = b"{\n  \"a\": 1,\n  \"b\": [2, 3]\n}";
+
+        assert_ne!(raw.digest(compact), raw.digest(pretty));
+        assert_eq!(parsed.digest(compact), parsed.digest(pretty));
+    }
+
+    #[test]
+    fn test_canonical_input_normalizes_keys_and_numbers() {
+        let canonical = detection(ChangeHash::Fnv1a, ChangeInput::Canonical);
+        let mut out = String::new();
+        write_canonical(
+            &serde_json::from_str(r#"{"b": [1.0, 2.5, -0.0], "a": {"y": 1e2, "x": "\""}}"#)
+                .unwrap(),
+            &mut out,
+        );
+
+        assert_eq!(out, r#"{"a":{"x":"\"","y":100},"b":[1,2.5,0]}"#);
+        assert_eq!(
+            canonical.digest(br#"{"a": 1, "b": 2.0}"#),
+            canonical.digest(br#"{"b": 2, "a": 1.0}"#)
+        );
+    }
+
+    #[test]
+    fn test_ignored_pointers_do_not_count_as_changes() {
+        let detection = ChangeDetection {
+            ignore: vec![
+                "/meta/generated_at".into(),
+                "/request_id".into(),
+                "/a~1b".into(),
+            ],
+            ..detection(ChangeHash::Fnv1a, ChangeInput::Raw)
+        };
+        let first = br#"{"meta":{"generated_at":1,"v":1},"request_id":"x","a/b":1,"data":[1]}"#;
+        let second = br#"{"meta":{"generated_at":2,"v":1},"request_id":"y","a/b":2,"data":[1]}"#;
+        let changed = br#"{"meta":{"generated_at":3,"v":1},"request_id":"z","a/b":3,"data":[2]}"#;
+
+        assert_eq!(detection.digest(first), detection.digest(second));
+        assert_ne!(detection.digest(first), detection.digest(changed));
+    }
+
+    #[test]
+    fn test_hash_choice_is_honoured() {
+        let custom = detection(
+            ChangeHash::custom(|bytes| bytes.len() as u64),
+            ChangeInput::Raw,
+        );
+
+        assert_eq!(custom.digest(b"12345"), 5);
+        assert_eq!(
+            detection(ChangeHash::default(), ChangeInput::Raw).digest(b"a"),
+            0xaf63_dc4c_8601_ec8c
+        );
+        #[cfg(feature = "xxhash")]
+        assert_eq!(
+            detection(ChangeHash::Xxh3, ChangeInput::Raw).digest(b"a"),
+            0xe6c6_32b6_1e96_4e1f
+        );
+        #[cfg(feature = "blake3")]
+        assert_eq!(
+            detection(ChangeHash::Blake3, ChangeInput::Raw).digest(b"a"),
+            0x53a4_69d9_dd2f_7617
+        );
+        assert_eq!(ChangeDetection::default().digest(b"a"), 0);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::net::{FamilyResolver, HttpVersion, IpFamily};
+use crate::{
+    POOL_IDLE_TIMEOUT_SECS, POOL_MAX_IDLE_PER_HOST, REQUEST_TIMEOUT_MS, TCP_KEEPALIVE_SECS,
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING};
+use reqwest::Client;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ClientConfig {
+    pub(crate) pool_max_idle_per_host: usize,
+    pub(crate) pool_idle_timeout_secs: u64,
+    pub(crate) request_timeout_ms: u64,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) local_address: Option<IpAddr>,
+    pub(crate) ip_family: IpFamily,
+    pub(crate) http_version: HttpVersion,
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "illumos",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "solaris",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "watchos",
+    ))]
+    pub(crate) interface: Option<String>,
+    pub(crate) tcp_keepalive_secs: u64,
+    pub(crate) tcp_keepalive_interval_secs: Option<u64>,
+    pub(crate) tcp_keepalive_retries: Option<u32>,
+    pub(crate) tcp_nodelay: bool,
+    pub(crate) max_connection_age: Option<Duration>,
+    pub(crate) tls_info: bool,
+    pub(crate) track_redirects: bool,
+    pub(crate) headers: Vec<(String, String)>,
+    #[cfg(feature = "gzip")]
+    pub(crate) gzip: Option<bool>,
+    #[cfg(feature = "brotli")]
+    pub(crate) brotli: Option<bool>,
+    #[cfg(feature = "zstd")]
+    pub(crate) zstd: Option<bool>,
+    #[cfg(feature = "deflate")]
+    pub(crate) deflate: Option<bool>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: POOL_MAX_IDLE_PER_HOST,
+            pool_idle_timeout_secs: POOL_IDLE_TIMEOUT_SECS,
+            request_timeout_ms: REQUEST_TIMEOUT_MS,
+            connect_timeout: None,
+            local_address: None,
+            ip_family: IpFamily::Any,
+            http_version: HttpVersion::Auto,
+            #[cfg(any(
+                target_os = "android",
+                target_os = "fuchsia",
+                target_os = "illumos",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "solaris",
+                target_os = "tvos",
+                target_os = "visionos",
+                target_os = "watchos",
+            ))]
+            interface: None,
+            tcp_keepalive_secs: TCP_KEEPALIVE_SECS,
+            tcp_keepalive_interval_secs: None,
+            tcp_keepalive_retries: None,
+            tcp_nodelay: true,
+            max_connection_age: None,
+            tls_info: false,
+            track_redirects: false,
+            headers: Vec::new(),
+            #[cfg(feature = "gzip")]
+            gzip: None,
+            #[cfg(feature = "brotli")]
+            brotli: None,
+            #[cfg(feature = "zstd")]
+            zstd: None,
+            #[cfg(feature = "deflate")]
+            deflate: None,
+        }
+    }
+}
+
+impl ClientConfig {
+    // Headers are validated when the poller is built, so anything invalid here is skipped.
+    fn header_map(&self) -> HeaderMap {
+        let mut headers: HeaderMap = self
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+        if let Some(accepted) = self.accept_encoding() {
+            headers.entry(ACCEPT_ENCODING).or_insert(accepted);
+        }
+        headers
+    }
+
+    // The poller decodes bodies itself (see `ContentDecoder`), so it also asks for them: every
+    // compiled-in algorithm that was not switched off.
+    fn accept_encoding(&self) -> Option<HeaderValue> {
+        let algorithms: &[(Option<bool>, &str)] = &[
+            #[cfg(feature = "gzip")]
+            (self.gzip, "gzip"),
+            #[cfg(feature = "zstd")]
+            (self.zstd, "zstd"),
+            #[cfg(feature = "brotli")]
+            (self.brotli, "br"),
+            #[cfg(feature = "deflate")]
+            (self.deflate, "deflate"),
+        ];
+        let accepted: Vec<&str> = algorithms
+            .iter()
+            .filter(|(enabled, _)| *enabled != Some(false))
+            .map(|(_, name)| *name)
+            .collect();
+        if accepted.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&accepted.join(", ")).ok()
+    }
+
+    // What every request sends as `Accept-Encoding`: the configured header, or the default above.
+    pub(crate) fn advertised_encodings(&self) -> Option<HeaderValue> {
+        self.header_map().remove(ACCEPT_ENCODING)
+    }
+
+    pub(crate) fn build(&self) -> Result<Client, reqwest::Error> {
+        let client = Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(self.pool_idle_timeout_secs))
+            .timeout(Duration::from_millis(self.request_timeout_ms))
+            .tcp_keepalive(Duration::from_secs(self.tcp_keepalive_secs))
+            .tcp_keepalive_interval(self.tcp_keepalive_interval_secs.map(Duration::from_secs))
+            .tcp_keepalive_retries(self.tcp_keepalive_retries)
+            .tcp_nodelay(self.tcp_nodelay)
+            .tls_info(self.tls_info)
+            .default_headers(self.header_map());
+        let client = match self.connect_timeout {
+            Some(timeout) => client.connect_timeout(timeout),
+            None => client,
+        };
+        let client = match self.track_redirects {
+            true => client.redirect(crate::redirect::policy()),
+            false => client,
+        };
+        let client = client.local_address(self.local_address);
+        let client = match self.ip_family {
+            IpFamily::Any => client,
+            family => client.dns_resolver(Arc::new(FamilyResolver::new(family))),
+        };
+        let client = match self.http_version {
+            HttpVersion::Auto => client,
+            HttpVersion::Http1Only => client.http1_only(),
+            HttpVersion::Http2PriorKnowledge => client.http2_prior_knowledge(),
+        };
+        #[cfg(any(
+            target_os = "android",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "solaris",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "watchos",
+        ))]
+        let client = match &self.interface {
+            Some(interface) => client.interface(interface),
+            None => client,
+        };
+        client.build()
+    }
+}
+
+// reqwest has no notion of connection age, so recycling means replacing the whole client: the
+// old pool is dropped once in-flight requests finish and the new one resolves DNS afresh.
+#[derive(Clone)]
+pub(crate) struct ManagedClient {
+    config: Arc<ClientConfig>,
+    current: Arc<RwLock<(Client, Instant)>>,
+}
+
+impl ManagedClient {
+    pub(crate) fn new(config: ClientConfig) -> Result<Self, reqwest::Error> {
+        let client = config.build()?;
+        Ok(Self {
+            config: Arc::new(config),
+            current: Arc::new(RwLock::new((client, Instant::now()))),
+        })
+    }
+
+    // `log` is the caller's `internal_logging` setting, since pollers from one `PollerFactory`
+    // share this client but not their logging.
+    pub(crate) fn get(&self, log: bool) -> Client {
+        let (client, created) = self.current.read().unwrap().clone();
+        match self.config.max_connection_age {
+            Some(max_age) if created.elapsed() >= max_age => {
+                self.recycle(created, log).unwrap_or(client)
+            }
+            _ => client,
+        }
+    }
+
+    fn recycle(&self, expected: Instant, log: bool) -> Option<Client> {
+        let mut current = self.current.write().unwrap();
+        if current.1 != expected {
+            // Another caller already replaced it.
+            return Some(current.0.clone());
+        }
+        match self.config.build() {
+            Ok(client) => {
+                if log {
+                    tracing::debug!("Recycling HTTP client connections");
+                }
+                *current = (client.clone(), Instant::now());
+                Some(client)
+            }
+            Err(e) => {
+                if log {
+                    tracing::warn!("Failed to recycle HTTP client: {:?}", e);
+                }
+                None
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn created(&self) -> Instant {
+        self.current.read().unwrap().1
+    }
+}
+
+// The client behind a `PollerFactory`, created when the first of its pollers is built.
+#[derive(Default)]
+pub(crate) struct SharedClient {
+    client: Mutex<Option<ManagedClient>>,
+}
+
+impl SharedClient {
+    pub(crate) fn get(&self, config: ClientConfig) -> Result<ManagedClient, reqwest::Error> {
+        let mut client = self.client.lock().unwrap();
+        if let Some(client) = client.as_ref() {
+            return Ok(client.clone());
+        }
+        Ok(client.insert(ManagedClient::new(config)?).clone())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn same(&self, other: &ManagedClient) -> bool {
+        let client = self.client.lock().unwrap();
+        client
+            .as_ref()
+            .is_some_and(|client| Arc::ptr_eq(&client.current, &other.current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_is_kept_without_max_age() {
+        let client = ManagedClient::new(ClientConfig::default()).unwrap();
+        let created = client.created();
+
+        client.get(false);
+        assert_eq!(client.created(), created);
+    }
+
+    #[test]
+    fn test_client_is_recycled_after_max_age() {
+        let client = ManagedClient::new(ClientConfig {
+            max_connection_age: Some(Duration::from_millis(10)),
+            ..ClientConfig::default()
+        })
+        .unwrap();
+        let created = client.created();
+
+        client.get(false);
+        assert_eq!(client.created(), created);
+        std::thread::sleep(Duration::from_millis(15));
+        client.get(false);
+        assert!(client.created() > created);
+    }
+}
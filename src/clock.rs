@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant, SystemTime};
+
+pub(crate) struct ClockGapDetector {
+    threshold: Duration,
+    last: Option<(Instant, SystemTime)>,
+}
+
+impl ClockGapDetector {
+    pub(crate) fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            last: None,
+        }
+    }
+
+    // `idle_from` is the point the loop could have been woken at: the later of the scheduled
+    // tick and the end of the previous cycle. Any delay past it was not caused by our own work,
+    // and any disagreement between the monotonic and wall clocks means the host slept or the
+    // system clock was adjusted.
+    pub(crate) fn observe(
+        &mut self,
+        now: Instant,
+        wall: SystemTime,
+        idle_from: Instant,
+    ) -> Option<Duration> {
+        let previous = self.last.replace((now, wall));
+        if self.threshold.is_zero() {
+            return None;
+        }
+        let (last_mono, last_wall) = previous?;
+
+        let frozen = now.saturating_duration_since(idle_from);
+        let mono_delta = now.saturating_duration_since(last_mono);
+        let skew = match wall.duration_since(last_wall) {
+            Ok(wall_delta) => wall_delta.abs_diff(mono_delta),
+            Err(backwards) => backwards.duration() + mono_delta,
+        };
+
+        let gap = frozen.max(skew);
+        (gap >= self.threshold).then_some(gap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(5);
+
+    fn start(detector: &mut ClockGapDetector) -> (Instant, SystemTime) {
+        let mono = Instant::now();
+        let wall = SystemTime::now();
+        assert_eq!(detector.observe(mono, wall, mono), None);
+        (mono, wall)
+    }
+
+    #[test]
+    fn test_regular_ticks_report_no_gap() {
+        let mut detector = ClockGapDetector::new(THRESHOLD);
+        let (mono, wall) = start(&mut detector);
+        let step = Duration::from_millis(500);
+
+        assert_eq!(
+            detector.observe(mono + step, wall + step, mono + step),
+            None
+        );
+    }
+
+    #[test]
+    fn test_suspend_is_detected_from_wall_clock_jump() {
+        let mut detector = ClockGapDetector::new(THRESHOLD);
+        let (mono, wall) = start(&mut detector);
+        let step = Duration::from_millis(500);
+        let slept = Duration::from_secs(3600);
+
+        let gap = detector.observe(mono + step, wall + step + slept, mono + step);
+        assert_eq!(gap, Some(slept));
+    }
+
+    #[test]
+    fn test_backwards_clock_adjustment_is_detected() {
+        let mut detector = ClockGapDetector::new(THRESHOLD);
+        let (mono, wall) = start(&mut detector);
+        let step = Duration::from_millis(500);
+
+        let gap = detector.observe(mono + step, wall - Duration::from_secs(60), mono + step);
+        assert_eq!(gap, Some(Duration::from_secs(60) + step));
+    }
+
+    #[test]
+    fn test_frozen_process_is_detected_from_late_wakeup() {
+        let mut detector = ClockGapDetector::new(THRESHOLD);
+        let (mono, wall) = start(&mut detector);
+        let paused = Duration::from_secs(30);
+        let now = mono + paused;
+
+        let gap = detector.observe(now, wall + paused, mono + Duration::from_millis(500));
+        assert_eq!(gap, Some(paused - Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_detection() {
+        let mut detector = ClockGapDetector::new(Duration::ZERO);
+        let (mono, wall) = start(&mut detector);
+
+        let gap = detector.observe(mono, wall + Duration::from_secs(3600), mono);
+        assert_eq!(gap, None);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    BuildError, ConcurrencyLimit, ConfigError, ConnectFailure, JsonPollerBuilder, Payload,
+    PollerGroup, Priority, Stagger, StatusAction, UnknownFields,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+// Mirrors the builder for the options a deployment typically tunes. The types only implement
+// `Deserialize`, so TOML or YAML files work as well through the matching serde crate.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupConfig {
+    pub pollers: Vec<PollerConfig>,
+    // Caps how many pollers of the group may have requests in flight at once.
+    #[serde(default)]
+    pub max_in_flight: Option<usize>,
+    // Spreads first polls across at most this window; 0 disables staggering (default: interval).
+    #[serde(default)]
+    pub stagger_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PollerConfig {
+    pub name: Option<String>,
+    pub labels: Vec<String>,
+    pub url: String,
+    pub interval_ms: Option<u64>,
+    pub initial_delay_ms: Option<u64>,
+    pub value_ttl_ms: Option<u64>,
+    pub request_timeout_ms: Option<u64>,
+    pub connect_timeout_ms: Option<u64>,
+    pub headers: BTreeMap<String, String>,
+    pub query: BTreeMap<String, String>,
+    pub status_retries: Option<u32>,
+    pub retry_delay_ms: Option<u64>,
+    pub max_backoff_ms: Option<u64>,
+    pub backoff_on_failure: Option<bool>,
+    pub on_status: BTreeMap<u16, StatusAction>,
+    pub on_connect_failure: BTreeMap<ConnectFailure, StatusAction>,
+    pub unknown_fields: Option<UnknownFields>,
+    // Schedule and report without fetching, to try out a config change on a live fleet.
+    pub dry_run: bool,
+    // Scheduling class under the group's `max_in_flight`.
+    pub priority: Priority,
+}
+
+impl GroupConfig {
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn build<T>(&self) -> Result<PollerGroup<T>, ConfigError>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let limit = self.limit()?;
+        let mut group = PollerGroup::new();
+        group.set_stagger(self.stagger());
+        for config in &self.pollers {
+            let mut builder = config.builder();
+            if let Some(limit) = &limit {
+                builder = builder.concurrency_limit(limit.clone());
+            }
+            group.insert(builder.build()?)?;
+        }
+        Ok(group)
+    }
+}
+
+impl GroupConfig {
+    // A limit of zero would leave every poller waiting for a permit forever.
+    pub(crate) fn limit(&self) -> Result<Option<ConcurrencyLimit>, ConfigError> {
+        if self.max_in_flight == Some(0) {
+            return Err(ConfigError::Build(BuildError::InvalidConfig(
+                "max_in_flight must be greater than zero".into(),
+            )));
+        }
+        Ok(self.max_in_flight.map(ConcurrencyLimit::new))
+    }
+
+    pub(crate) fn stagger(&self) -> Stagger {
+        match self.stagger_ms {
+            Some(0) => Stagger::Disabled,
+            Some(ms) => Stagger::Window(Duration::from_millis(ms)),
+            None => Stagger::Interval,
+        }
+    }
+}
+
+impl PollerConfig {
+    // The name the poller is registered under, matching `JsonPoller::name`.
+    pub fn resolved_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.url)
+    }
+
+    pub fn builder<T: Payload + 'static>(&self) -> JsonPollerBuilder<T> {
+        let mut builder = JsonPollerBuilder::new(&self.url);
+        if let Some(name) = &self.name {
+            builder = builder.name(name);
+        }
+        for label in &self.labels {
+            builder = builder.label(label);
+        }
+        if let Some(ms) = self.interval_ms {
+            builder = builder.poll_interval_ms(ms);
+        }
+        if let Some(ms) = self.initial_delay_ms {
+            builder = builder.initial_delay(Duration::from_millis(ms));
+        }
+        if let Some(ms) = self.value_ttl_ms {
+            builder = builder.value_ttl(Duration::from_millis(ms));
+        }
+        if let Some(ms) = self.request_timeout_ms {
+            builder = builder.request_timeout_ms(ms);
+        }
+        if let Some(ms) = self.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(ms));
+        }
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        if !self.query.is_empty() {
+            let query: Vec<_> = self.query.iter().collect();
+            builder = builder.query(&query);
+        }
+        if let Some(retries) = self.status_retries {
+            builder = builder.status_retries(retries);
+        }
+        if let Some(ms) = self.retry_delay_ms {
+            builder = builder.retry_delay_ms(ms);
+        }
+        if let Some(ms) = self.max_backoff_ms {
+            builder = builder.max_backoff_ms(ms);
+        }
+        if let Some(enabled) = self.backoff_on_failure {
+            builder = builder.backoff_on_failure(enabled);
+        }
+        for (&status, &action) in &self.on_status {
+            builder = builder.on_status(status, action);
+        }
+        for (&failure, &action) in &self.on_connect_failure {
+            builder = builder.on_connect_failure(failure, action);
+        }
+        if self.dry_run {
+            builder = builder.dry_run(true);
+        }
+        if let Some(mode) = self.unknown_fields {
+            builder = builder.unknown_fields(mode);
+        }
+        builder.priority(self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, TestResponse};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_group_is_built_from_json() {
+        let config = GroupConfig::from_json(
+            r#"{"max_in_flight": 2, "pollers": [
+                {"name": "prices", "url": "http://127.0.0.1:1/prices", "interval_ms": 250,
+                 "headers": {"Authorization": "Bearer x"}, "on_status": {"404": "empty"},
+                 "priority": "high", "labels": ["pricing"]},
+                {"url": "http://127.0.0.1:1/rates"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.max_in_flight, Some(2));
+        assert_eq!(config.pollers[0].interval_ms, Some(250));
+        assert_eq!(config.pollers[0].on_status[&404], StatusAction::Empty);
+        assert_eq!(config.pollers[0].priority, Priority::High);
+        assert_eq!(config.pollers[1].priority, Priority::Normal);
+        assert_eq!(config.pollers[0].labels, ["pricing"]);
+        let group = config.build::<serde_json::Value>().unwrap();
+        assert_eq!(
+            group.names().collect::<Vec<_>>(),
+            ["http://127.0.0.1:1/rates", "prices"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        assert!(matches!(
+            GroupConfig::from_json(r#"{"pollers": [{"url": "x", "interval": 1}]}"#),
+            Err(ConfigError::Parse(_))
+        ));
+        let duplicate =
+            GroupConfig::from_json(r#"{"pollers": [{"url": "http://a"}, {"url": "http://a"}]}"#)
+                .unwrap();
+        assert!(matches!(
+            duplicate.build::<serde_json::Value>(),
+            Err(ConfigError::DuplicateName(_))
+        ));
+    }
+
+    #[test]
+    fn test_zero_max_in_flight_is_rejected() {
+        let config = GroupConfig::from_json(r#"{"max_in_flight": 0, "pollers": []}"#).unwrap();
+        assert!(matches!(
+            config.build::<serde_json::Value>(),
+            Err(ConfigError::Build(BuildError::InvalidConfig(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_group_spawns_every_poller_with_its_headers() {
+        let prices = serve(|_, _| TestResponse::json("1")).await;
+        let rates = serve(|_, _| TestResponse::json("2")).await;
+        let config = GroupConfig::from_json(&format!(
+            r#"{{"pollers": [
+                {{"name": "prices", "url": "{}", "interval_ms": 10, "headers": {{"X-Api-Key": "k"}}}},
+                {{"name": "rates", "url": "{}", "interval_ms": 10}}
+            ]}}"#,
+            prices.url, rates.url
+        ))
+        .unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let task = config.build::<u64>().unwrap().spawn(move |name, value| {
+            recorder.lock().unwrap().push((name.to_string(), value));
+            async { Ok::<_, ()>(()) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        task.shutdown(Duration::from_secs(1)).await;
+
+        let seen = seen.lock().unwrap();
+        assert!(seen.contains(&("prices".to_string(), 1)));
+        assert!(seen.contains(&("rates".to_string(), 2)));
+        assert!(prices.requests()[0].to_lowercase().contains("x-api-key: k"));
+        assert!(!rates.requests()[0].to_lowercase().contains("x-api-key"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+
+// Pauses, resumes or triggers a poller from outside its loop. Clones of a poller (including the
+// one moved into `spawn`) share the same control.
+#[derive(Debug, Clone)]
+pub struct PollControl {
+    paused: Arc<watch::Sender<bool>>,
+    trigger: Arc<watch::Sender<u64>>,
+}
+
+impl Default for PollControl {
+    fn default() -> Self {
+        Self {
+            paused: Arc::new(watch::channel(false).0),
+            trigger: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+
+impl PollControl {
+    // A paused poller finishes the cycle in flight and then idles until resumed.
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    // Runs the next poll without waiting for the interval. While a cycle is in flight the
+    // trigger is kept and fires once it has finished; a paused poller ignores it.
+    pub fn trigger_now(&self) {
+        self.trigger
+            .send_modify(|count| *count = count.wrapping_add(1));
+    }
+
+    pub(crate) fn triggers(&self) -> Triggers {
+        Triggers(self.trigger.subscribe())
+    }
+
+    pub(crate) async fn resumed(&self) {
+        let mut paused = self.paused.subscribe();
+        // The sender lives as long as `self`, so this only returns once resumed.
+        let _ = paused.wait_for(|paused| !paused).await;
+    }
+}
+
+pub(crate) struct Triggers(watch::Receiver<u64>);
+
+impl Triggers {
+    pub(crate) async fn next(&mut self) {
+        if self.0.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    // Drops a trigger that arrived while paused or during the cycle it would have started.
+    pub(crate) fn clear(&mut self) {
+        self.0.borrow_and_update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_trigger_is_kept_until_observed() {
+        let control = PollControl::default();
+        let mut triggers = control.triggers();
+
+        control.trigger_now();
+        control.trigger_now();
+        tokio::time::timeout(Duration::from_millis(50), triggers.next())
+            .await
+            .unwrap();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), triggers.next())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumed_waits_for_resume() {
+        let control = PollControl::default();
+        control.pause();
+        assert!(control.is_paused());
+        let waiting = tokio::spawn({
+            let control = control.clone();
+            async move { control.resumed().await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        control.resume();
+        tokio::time::timeout(Duration::from_millis(50), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
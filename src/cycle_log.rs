@@ -0,0 +1,158 @@
+use crate::watermark::format_rfc3339;
+use crate::PollOutcome;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+// What one poll cycle looked like, as written by `json_log`.
+pub(crate) struct Cycle<'a> {
+    pub(crate) tick: u64,
+    pub(crate) outcome: PollOutcome,
+    pub(crate) status: Option<u16>,
+    pub(crate) latency: Duration,
+    pub(crate) attempts: u32,
+    pub(crate) bytes: Option<u64>,
+    pub(crate) digest: Option<u64>,
+    pub(crate) request_id: Option<&'a str>,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    timestamp: String,
+    poller: &'a str,
+    tick: u64,
+    outcome: &'static str,
+    status: Option<u16>,
+    latency_ms: f64,
+    attempts: u32,
+    bytes: Option<u64>,
+    changed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// One NDJSON line per poll cycle. `changed` compares the payload digest with the previous
+// successful poll, so it is `null` for cycles without a body.
+pub(crate) struct CycleLog {
+    writer: Mutex<Box<dyn Write + Send>>,
+    last_digest: Mutex<Option<u64>>,
+}
+
+impl CycleLog {
+    pub(crate) fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+            last_digest: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn write(&self, poller: &str, cycle: Cycle<'_>) {
+        let changed = match (cycle.outcome, cycle.digest) {
+            (PollOutcome::Unchanged, _) => Some(false),
+            (_, Some(digest)) => {
+                let previous = self.last_digest.lock().unwrap().replace(digest);
+                Some(previous != Some(digest))
+            }
+            _ => None,
+        };
+        let line = Line {
+            timestamp: format_rfc3339(SystemTime::now()),
+            poller,
+            tick: cycle.tick,
+            outcome: match cycle.outcome {
+                PollOutcome::Success => "success",
+                PollOutcome::Empty => "empty",
+                PollOutcome::Unchanged => "unchanged",
+                PollOutcome::Failed => "failed",
+                PollOutcome::DryRun => "dry_run",
+            },
+            status: cycle.status,
+            latency_ms: cycle.latency.as_secs_f64() * 1000.0,
+            attempts: cycle.attempts,
+            bytes: cycle.bytes,
+            changed,
+            request_id: cycle.request_id,
+            error: cycle.error,
+        };
+        let Ok(mut bytes) = serde_json::to_vec(&line) else {
+            return;
+        };
+        bytes.push(b'\n');
+        // A failing log destination must not fail the poll.
+        let mut writer = self.writer.lock().unwrap();
+        let _ = writer.write_all(&bytes).and_then(|()| writer.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn success(tick: u64, digest: u64) -> Cycle<'static> {
+        Cycle {
+            tick,
+            outcome: PollOutcome::Success,
+            status: Some(200),
+            latency: Duration::from_micros(1500),
+            attempts: 1,
+            bytes: Some(12),
+            digest: Some(digest),
+            request_id: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_one_line_per_cycle_with_change_flag() {
+        let out = Shared::default();
+        let log = CycleLog::new(out.clone());
+        log.write("prices", success(1, 7));
+        log.write("prices", success(2, 7));
+        log.write(
+            "prices",
+            Cycle {
+                outcome: PollOutcome::Failed,
+                status: Some(503),
+                bytes: None,
+                digest: None,
+                error: Some("HTTP status 503".into()),
+                ..success(3, 0)
+            },
+        );
+
+        let written = out.0.lock().unwrap().clone();
+        let lines: Vec<Value> = String::from_utf8(written)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["poller"], "prices");
+        assert_eq!(lines[0]["latency_ms"], 1.5);
+        assert_eq!(lines[0]["changed"], true);
+        assert_eq!(lines[1]["changed"], false);
+        assert_eq!(lines[2]["outcome"], "failed");
+        assert_eq!(lines[2]["changed"], Value::Null);
+        assert_eq!(lines[2]["error"], "HTTP status 503");
+        assert!(lines[2]["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+}
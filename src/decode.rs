@@ -0,0 +1,413 @@
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH};
+use std::io;
+
+#[cfg(any(feature = "gzip", feature = "brotli", feature = "zstd"))]
+use std::io::Write;
+
+// The encodings a poller advertised in `Accept-Encoding`, and how large a body it decodes.
+#[derive(Debug, Clone)]
+pub(crate) struct Decoding {
+    accepted: Vec<String>,
+    max_decoded_bytes: u64,
+}
+
+impl Decoding {
+    pub(crate) fn new(accept_encoding: Option<&HeaderValue>, max_decoded_bytes: u64) -> Self {
+        let accepted = accept_encoding
+            .and_then(|value| value.to_str().ok())
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .filter_map(|coding| {
+                let mut params = coding.split(';');
+                let name = params.next()?.trim().to_ascii_lowercase();
+                let refused = params.any(|param| {
+                    param
+                        .trim()
+                        .strip_prefix("q=")
+                        .and_then(|q| q.parse::<f32>().ok())
+                        == Some(0.0)
+                });
+                (!name.is_empty() && !refused).then_some(name)
+            })
+            .collect();
+        Self {
+            accepted,
+            max_decoded_bytes,
+        }
+    }
+
+    fn accepts(&self, encoding: &str) -> bool {
+        let alias = match encoding {
+            "x-gzip" => "gzip",
+            other => other,
+        };
+        self.accepted
+            .iter()
+            .any(|accepted| accepted == alias || accepted == "*")
+    }
+}
+
+// Undoes the `Content-Encoding` of a response as it is read. reqwest's own decompression is left
+// off so the poller sees the bytes as they came over the wire and can count them. An encoding
+// the request did not advertise is an error; one it did advertise but has no compiled-in decoder
+// for is passed through untouched, as reqwest would.
+pub(crate) struct ContentDecoder {
+    codec: Codec,
+    decoded: u64,
+    limit: u64,
+}
+
+enum Codec {
+    Identity,
+    #[cfg(feature = "gzip")]
+    Gzip(Box<flate2::write::GzDecoder<Vec<u8>>>),
+    #[cfg(feature = "deflate")]
+    Deflate(Box<Inflate>),
+    #[cfg(feature = "brotli")]
+    Brotli(Box<brotli_decompressor::DecompressorWriter<Vec<u8>>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::zio::Writer<Vec<u8>, zstd::stream::raw::Decoder<'static>>),
+}
+
+// zlib inflation that remembers whether the stream reached its end, which flate2's writers
+// don't report.
+#[cfg(feature = "deflate")]
+pub(crate) struct Inflate {
+    state: flate2::Decompress,
+    ended: bool,
+}
+
+#[cfg(feature = "deflate")]
+impl Inflate {
+    fn new() -> Self {
+        Self {
+            state: flate2::Decompress::new(true),
+            ended: false,
+        }
+    }
+
+    fn run(&mut self, mut input: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        while !self.ended {
+            out.reserve(4096);
+            let (read, written) = (self.state.total_in(), self.state.total_out());
+            let status = self
+                .state
+                .decompress_vec(input, out, flate2::FlushDecompress::None)
+                .map_err(io::Error::other)?;
+            input = &input[(self.state.total_in() - read) as usize..];
+            self.ended = status == flate2::Status::StreamEnd;
+            if self.state.total_in() == read && self.state.total_out() == written {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ContentDecoder {
+    // Like reqwest, drops `Content-Encoding` and `Content-Length` from a body it decodes, since
+    // neither describes what the callback receives.
+    #[cfg_attr(
+        not(any(
+            feature = "gzip",
+            feature = "brotli",
+            feature = "zstd",
+            feature = "deflate"
+        )),
+        allow(clippy::match_single_binding)
+    )]
+    pub(crate) fn for_response(headers: &mut HeaderMap, decoding: &Decoding) -> io::Result<Self> {
+        let encoding = headers
+            .get(CONTENT_ENCODING)
+            .map(|value| {
+                value
+                    .to_str()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .filter(|encoding| !encoding.is_empty() && encoding != "identity");
+        if let Some(encoding) = encoding.as_deref().filter(|e| !decoding.accepts(e)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("response uses Content-Encoding `{encoding}`, which was not requested"),
+            ));
+        }
+        let codec = match encoding.as_deref() {
+            #[cfg(feature = "gzip")]
+            Some("gzip" | "x-gzip") => {
+                Codec::Gzip(Box::new(flate2::write::GzDecoder::new(Vec::new())))
+            }
+            #[cfg(feature = "deflate")]
+            Some("deflate") => Codec::Deflate(Box::new(Inflate::new())),
+            #[cfg(feature = "brotli")]
+            Some("br") => Codec::Brotli(Box::new(brotli_decompressor::DecompressorWriter::new(
+                Vec::new(),
+                4096,
+            ))),
+            #[cfg(feature = "zstd")]
+            Some("zstd") => Codec::Zstd(zstd::stream::zio::Writer::new(
+                Vec::new(),
+                zstd::stream::raw::Decoder::new()?,
+            )),
+            _ => Codec::Identity,
+        };
+        let decoder = Self {
+            codec,
+            decoded: 0,
+            limit: decoding.max_decoded_bytes,
+        };
+        if !decoder.is_identity() {
+            headers.remove(CONTENT_ENCODING);
+            headers.remove(CONTENT_LENGTH);
+        }
+        Ok(decoder)
+    }
+
+    // Counts bytes decoded from an encoded body, which may add up to `max_decoded_bytes`.
+    fn count(&mut self, decoded: usize) -> io::Result<()> {
+        self.decoded += decoded as u64;
+        if self.decoded <= self.limit {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decoded body is larger than {} bytes", self.limit),
+        ))
+    }
+
+    // Appends what `chunk` decodes to onto `out`.
+    pub(crate) fn decode(&mut self, chunk: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        let from = out.len();
+        match &mut self.codec {
+            Codec::Identity => out.extend_from_slice(chunk),
+            #[cfg(feature = "gzip")]
+            Codec::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+            #[cfg(feature = "deflate")]
+            Codec::Deflate(inflate) => inflate.run(chunk, out)?,
+            #[cfg(feature = "brotli")]
+            Codec::Brotli(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+            #[cfg(feature = "zstd")]
+            Codec::Zstd(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                out.append(decoder.writer_mut());
+            }
+        }
+        if self.is_identity() {
+            return Ok(());
+        }
+        self.count(out.len() - from)
+    }
+
+    // Appends the rest of the body once the last chunk is in, and fails if the stream was cut
+    // short where the format can tell.
+    #[cfg_attr(
+        not(any(
+            feature = "gzip",
+            feature = "brotli",
+            feature = "zstd",
+            feature = "deflate"
+        )),
+        allow(unused_variables, clippy::ptr_arg)
+    )]
+    pub(crate) fn finish(&mut self, out: &mut Vec<u8>) -> io::Result<()> {
+        let from = out.len();
+        match std::mem::replace(&mut self.codec, Codec::Identity) {
+            Codec::Identity => {}
+            #[cfg(feature = "gzip")]
+            Codec::Gzip(mut decoder) => {
+                decoder.try_finish()?;
+                out.append(decoder.get_mut());
+            }
+            #[cfg(feature = "deflate")]
+            Codec::Deflate(mut inflate) => {
+                inflate.run(&[], out)?;
+                if !inflate.ended {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+            }
+            #[cfg(feature = "brotli")]
+            Codec::Brotli(decoder) => match decoder.into_inner() {
+                Ok(mut rest) => out.append(&mut rest),
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "brotli stream ended early",
+                    ))
+                }
+            },
+            // Fails with `UnexpectedEof` unless the last frame was complete.
+            #[cfg(feature = "zstd")]
+            Codec::Zstd(mut decoder) => {
+                decoder.finish()?;
+                out.append(decoder.writer_mut());
+            }
+        }
+        self.count(out.len() - from)
+    }
+
+    pub(crate) fn is_identity(&self) -> bool {
+        matches!(self.codec, Codec::Identity)
+    }
+
+    // Decodes a body read in one piece; an unencoded one is returned without copying.
+    pub(crate) fn decode_all(mut self, body: Bytes) -> io::Result<Bytes> {
+        if self.is_identity() {
+            return Ok(body);
+        }
+        let mut out = Vec::new();
+        self.decode(&body, &mut out)?;
+        self.finish(&mut out)?;
+        Ok(Bytes::from(out))
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "gzip",
+        feature = "brotli",
+        feature = "zstd",
+        feature = "deflate"
+    )
+))]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    #[cfg(any(feature = "gzip", feature = "brotli", feature = "deflate"))]
+    use std::io::Write;
+
+    fn json() -> Vec<u8> {
+        br#"{"prices":[1,2,3,4,5,6,7,8,9,10]}"#.repeat(20)
+    }
+
+    fn accepting(encodings: &str, max_decoded_bytes: u64) -> Decoding {
+        Decoding::new(
+            Some(&HeaderValue::from_str(encodings).unwrap()),
+            max_decoded_bytes,
+        )
+    }
+
+    fn headers(encoding: &str, len: usize) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_str(encoding).unwrap());
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(len));
+        headers
+    }
+
+    // Decodes `body` a few bytes at a time and checks it comes out as `json()`, then that the
+    // first half of it alone is reported as cut short.
+    fn assert_decodes(encoding: &str, body: &[u8]) {
+        let json = json();
+        assert!(body.len() < json.len());
+        let mut headers = headers(encoding, body.len());
+        let decoding = accepting(encoding, u64::MAX);
+        let mut decoder = ContentDecoder::for_response(&mut headers, &decoding).unwrap();
+        assert!(headers.get(CONTENT_ENCODING).is_none());
+        assert!(headers.get(CONTENT_LENGTH).is_none());
+        let mut out = Vec::new();
+        for chunk in body.chunks(3) {
+            decoder.decode(chunk, &mut out).unwrap();
+        }
+        decoder.finish(&mut out).unwrap();
+        assert_eq!(out, json);
+
+        let half = Bytes::copy_from_slice(&body[..body.len() / 2]);
+        let decoder =
+            ContentDecoder::for_response(&mut self::headers(encoding, half.len()), &decoding);
+        assert!(decoder.unwrap().decode_all(half).is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_bodies_are_decoded() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&json()).unwrap();
+        assert_decodes("gzip", &gzip.finish().unwrap());
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn test_deflate_bodies_are_decoded() {
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        zlib.write_all(&json()).unwrap();
+        assert_decodes("deflate", &zlib.finish().unwrap());
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn test_brotli_bodies_are_decoded() {
+        let mut br = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+        br.write_all(&json()).unwrap();
+        assert_decodes("br", &br.into_inner());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_bodies_are_decoded() {
+        assert_decodes("zstd", &zstd::stream::encode_all(&json()[..], 0).unwrap());
+    }
+
+    #[test]
+    fn test_unencoded_bodies_pass_through() {
+        let mut headers = headers("identity", 3);
+        let decoder = ContentDecoder::for_response(&mut headers, &accepting("gzip", 0)).unwrap();
+        assert!(decoder.is_identity());
+        assert!(headers.get(CONTENT_LENGTH).is_some());
+        assert_eq!(
+            decoder.decode_all(Bytes::from_static(b"[1]")).unwrap(),
+            "[1]"
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_unrequested_encodings_are_rejected() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&json()).unwrap();
+        let body = Bytes::from(gzip.finish().unwrap());
+        let decode = |decoding: &Decoding| {
+            let mut headers = headers("gzip", body.len());
+            ContentDecoder::for_response(&mut headers, decoding)?.decode_all(body.clone())
+        };
+
+        assert!(decode(&accepting("br, zstd", u64::MAX)).is_err());
+        assert!(decode(&accepting("gzip;q=0, br", u64::MAX)).is_err());
+        assert!(decode(&Decoding::new(None, u64::MAX)).is_err());
+        assert_eq!(
+            decode(&accepting("br, GZIP;q=0.5", u64::MAX)).unwrap(),
+            json()
+        );
+        assert_eq!(decode(&accepting("*", u64::MAX)).unwrap(), json());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_decoded_size_is_limited() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&vec![b' '; 1 << 20]).unwrap();
+        let body = gzip.finish().unwrap();
+        assert!(body.len() < 4096);
+
+        let mut headers = headers("gzip", body.len());
+        let decoding = accepting("gzip", 64 * 1024);
+        let mut decoder = ContentDecoder::for_response(&mut headers, &decoding).unwrap();
+        let mut out = Vec::new();
+        let error = body
+            .chunks(64)
+            .try_for_each(|chunk| decoder.decode(chunk, &mut out))
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(out.len() < 128 * 1024);
+    }
+}
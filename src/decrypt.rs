@@ -0,0 +1,155 @@
+use crate::{base64, IntegrityError};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_128_GCM, AES_256_GCM, NONCE_LEN};
+use serde::Deserialize;
+
+// How encrypted bodies are opened before parsing. The key length (16 or 32 bytes) selects
+// AES-128-GCM or AES-256-GCM.
+#[derive(Debug, Clone)]
+pub enum Decryption {
+    // Raw body: 12-byte nonce, then the ciphertext with its 16-byte tag appended.
+    AesGcm(Vec<u8>),
+    // Compact JWE with `"alg": "dir"` and `"enc": "A128GCM"` or `"A256GCM"`.
+    JweDirect(Vec<u8>),
+}
+
+#[derive(Deserialize)]
+struct JweHeader {
+    alg: String,
+    enc: String,
+}
+
+fn failed(reason: &str) -> IntegrityError {
+    IntegrityError::Undecryptable(reason.to_owned())
+}
+
+impl Decryption {
+    fn key(&self) -> &[u8] {
+        match self {
+            Self::AesGcm(key) | Self::JweDirect(key) => key,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self.key().len() {
+            16 | 32 => Ok(()),
+            len => Err(format!("AES-GCM keys are 16 or 32 bytes, not {len}")),
+        }
+    }
+
+    pub(crate) fn decrypt(&self, body: &[u8]) -> Result<Vec<u8>, IntegrityError> {
+        match self {
+            Self::AesGcm(key) => {
+                let (nonce, sealed) = body
+                    .split_at_checked(NONCE_LEN)
+                    .ok_or_else(|| failed("body is shorter than a nonce"))?;
+                open(key, nonce, Aad::from(&[][..]), sealed.to_vec())
+            }
+            Self::JweDirect(key) => {
+                let token = std::str::from_utf8(body.trim_ascii())
+                    .map_err(|_| failed("not a compact JWE"))?;
+                let parts: Vec<_> = token.split('.').collect();
+                let [header, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+                    return Err(failed("not a compact JWE"));
+                };
+                let decode = |part| base64::decode(part).ok_or_else(|| failed("not base64url"));
+                let parsed: JweHeader = serde_json::from_slice(&decode(header)?)
+                    .map_err(|_| failed("header is not a JOSE header"))?;
+                let expected = if key.len() == 16 {
+                    "A128GCM"
+                } else {
+                    "A256GCM"
+                };
+                if parsed.alg != "dir" || parsed.enc != expected || !encrypted_key.is_empty() {
+                    return Err(failed(&format!(
+                        "expected alg dir with enc {expected}, got {} with {}",
+                        parsed.alg, parsed.enc
+                    )));
+                }
+                let mut sealed = decode(ciphertext)?;
+                sealed.extend(decode(tag)?);
+                open(key, &decode(iv)?, Aad::from(header.as_bytes()), sealed)
+            }
+        }
+    }
+}
+
+fn open(
+    key: &[u8],
+    nonce: &[u8],
+    aad: Aad<&[u8]>,
+    mut sealed: Vec<u8>,
+) -> Result<Vec<u8>, IntegrityError> {
+    let algorithm = if key.len() == 16 {
+        &AES_128_GCM
+    } else {
+        &AES_256_GCM
+    };
+    let key = UnboundKey::new(algorithm, key).map_err(|_| failed("invalid key"))?;
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| failed("invalid nonce"))?;
+    let len = LessSafeKey::new(key)
+        .open_in_place(nonce, aad, &mut sealed)
+        .map_err(|_| failed("authentication failed"))?
+        .len();
+    sealed.truncate(len);
+    Ok(sealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seal(key: &[u8], nonce: [u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let algorithm = if key.len() == 16 {
+            &AES_128_GCM
+        } else {
+            &AES_256_GCM
+        };
+        let key = LessSafeKey::new(UnboundKey::new(algorithm, key).unwrap());
+        let mut sealed = plaintext.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut sealed,
+        )
+        .unwrap();
+        sealed
+    }
+
+    #[test]
+    fn test_raw_aes_gcm_body() {
+        let key = [7u8; 32];
+        let mut body = vec![1u8; NONCE_LEN];
+        body.extend(seal(&key, [1; NONCE_LEN], b"", br#"{"ok":true}"#));
+
+        let decryption = Decryption::AesGcm(key.to_vec());
+        assert_eq!(decryption.decrypt(&body).unwrap(), br#"{"ok":true}"#);
+        body[NONCE_LEN] ^= 1;
+        assert_eq!(
+            decryption.decrypt(&body),
+            Err(failed("authentication failed"))
+        );
+        assert!(Decryption::AesGcm(vec![0; 24]).validate().is_err());
+    }
+
+    #[test]
+    fn test_direct_jwe() {
+        let key = [9u8; 16];
+        let header = base64::encode_url(br#"{"alg":"dir","enc":"A128GCM"}"#);
+        let sealed = seal(&key, [2; NONCE_LEN], header.as_bytes(), br#"{"price":7}"#);
+        let (ciphertext, tag) = sealed.split_at(sealed.len() - 16);
+        let token = format!(
+            "{header}..{}.{}.{}",
+            base64::encode_url(&[2; NONCE_LEN]),
+            base64::encode_url(ciphertext),
+            base64::encode_url(tag)
+        );
+
+        let decryption = Decryption::JweDirect(key.to_vec());
+        assert_eq!(
+            decryption.decrypt(token.as_bytes()).unwrap(),
+            br#"{"price":7}"#
+        );
+        let wrong_enc = Decryption::JweDirect(vec![9; 32]);
+        assert!(wrong_enc.decrypt(token.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, Mutex};
+
+// Which key `dedup_items` forgets once its seen-set is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Eviction {
+    // The key delivered longest ago.
+    #[default]
+    Oldest,
+    // The key that has gone unseen the longest, so items that stay in the window are never
+    // forgotten while they keep showing up.
+    LeastRecentlySeen,
+}
+
+pub(crate) type KeyFn<T> = Arc<dyn Fn(&T, &RandomState) -> u64 + Send + Sync>;
+
+pub(crate) fn key_fn<T, K, F>(key: F) -> KeyFn<T>
+where
+    K: Hash,
+    F: Fn(&T) -> K + Send + Sync + 'static,
+{
+    Arc::new(move |item, state| state.hash_one(key(item)))
+}
+
+// Keys of the items delivered so far, shared by all clones of a poller.
+pub(crate) struct Dedup<T> {
+    key: KeyFn<T>,
+    state: RandomState,
+    seen: Mutex<SeenSet>,
+}
+
+impl<T> Dedup<T> {
+    pub(crate) fn new(key: KeyFn<T>, capacity: usize, eviction: Eviction) -> Self {
+        Self {
+            key,
+            state: RandomState::new(),
+            seen: Mutex::new(SeenSet::new(capacity, eviction)),
+        }
+    }
+
+    // True the first time an item's key comes along.
+    pub(crate) fn first_sighting(&self, item: &T) -> bool {
+        let key = (self.key)(item, &self.state);
+        self.seen.lock().unwrap().insert(key)
+    }
+}
+
+struct SeenSet {
+    capacity: usize,
+    eviction: Eviction,
+    // The sequence number each key was last touched at; queue entries with an older number are
+    // stale and skipped on eviction.
+    keys: HashMap<u64, u64>,
+    order: VecDeque<(u64, u64)>,
+    sequence: u64,
+}
+
+impl SeenSet {
+    fn new(capacity: usize, eviction: Eviction) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            eviction,
+            keys: HashMap::new(),
+            order: VecDeque::new(),
+            sequence: 0,
+        }
+    }
+
+    fn insert(&mut self, key: u64) -> bool {
+        self.sequence += 1;
+        if let Some(touched) = self.keys.get_mut(&key) {
+            if self.eviction == Eviction::LeastRecentlySeen {
+                *touched = self.sequence;
+                self.order.push_back((key, self.sequence));
+                if self.order.len() > 2 * self.capacity {
+                    let keys = &self.keys;
+                    self.order.retain(|(key, at)| keys.get(key) == Some(at));
+                }
+            }
+            return false;
+        }
+        self.keys.insert(key, self.sequence);
+        self.order.push_back((key, self.sequence));
+        while self.keys.len() > self.capacity {
+            let Some((oldest, at)) = self.order.pop_front() else {
+                break;
+            };
+            if self.keys.get(&oldest) == Some(&at) {
+                self.keys.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oldest_key_is_forgotten_first() {
+        let mut seen = SeenSet::new(2, Eviction::Oldest);
+        assert!(seen.insert(1));
+        assert!(seen.insert(2));
+        assert!(!seen.insert(1));
+        assert!(seen.insert(3));
+        assert!(seen.insert(1));
+        assert!(!seen.insert(3));
+    }
+
+    #[test]
+    fn test_recently_seen_keys_are_kept() {
+        let mut seen = SeenSet::new(2, Eviction::LeastRecentlySeen);
+        assert!(seen.insert(1));
+        assert!(seen.insert(2));
+        assert!(!seen.insert(1));
+        assert!(seen.insert(3));
+        assert!(!seen.insert(1));
+        assert!(seen.insert(2));
+        for _ in 0..10 {
+            assert!(!seen.insert(2));
+        }
+        assert!(seen.order.len() <= 4);
+    }
+
+    #[test]
+    fn test_items_are_keyed_by_the_key_fn() {
+        let dedup = Dedup::new(key_fn(|item: &(u32, &str)| item.0), 10, Eviction::Oldest);
+        assert!(dedup.first_sighting(&(1, "a")));
+        assert!(!dedup.first_sighting(&(1, "b")));
+        assert!(dedup.first_sighting(&(2, "a")));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::patch;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use serde_json::Value;
+use std::sync::Arc;
+
+type DeltaPredicate = Arc<dyn Fn(&HeaderMap, &Value) -> bool + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaFormat {
+    // RFC 7396, `application/merge-patch+json`.
+    MergePatch,
+    // RFC 6902, `application/json-patch+json`.
+    JsonPatch,
+}
+
+impl DeltaFormat {
+    fn media_type(self) -> &'static str {
+        match self {
+            Self::MergePatch => "application/merge-patch+json",
+            Self::JsonPatch => "application/json-patch+json",
+        }
+    }
+}
+
+// How `start_materialized` tells snapshots from deltas and recovers from a delta that does not
+// apply. By default a response is a delta when its Content-Type is the format's media type.
+#[derive(Clone)]
+pub struct DeltaFeed {
+    format: DeltaFormat,
+    is_delta: Option<DeltaPredicate>,
+    full_refresh: Option<(String, String)>,
+}
+
+impl DeltaFeed {
+    pub fn merge_patch() -> Self {
+        Self::new(DeltaFormat::MergePatch)
+    }
+
+    pub fn json_patch() -> Self {
+        Self::new(DeltaFormat::JsonPatch)
+    }
+
+    fn new(format: DeltaFormat) -> Self {
+        Self {
+            format,
+            is_delta: None,
+            full_refresh: None,
+        }
+    }
+
+    pub fn delta_when<F>(mut self, is_delta: F) -> Self
+    where
+        F: Fn(&HeaderMap, &Value) -> bool + Send + Sync + 'static,
+    {
+        self.is_delta = Some(Arc::new(is_delta));
+        self
+    }
+
+    // Query parameter that makes the server answer with a full snapshot. When set, a failed
+    // delta is followed by an immediate request with it in the same poll.
+    pub fn full_refresh_query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.full_refresh = Some((key.into(), value.into()));
+        self
+    }
+
+    pub(crate) fn is_delta(&self, headers: &HeaderMap, body: &Value) -> bool {
+        if let Some(is_delta) = &self.is_delta {
+            return is_delta(headers, body);
+        }
+        headers
+            .get_all(CONTENT_TYPE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.starts_with(self.format.media_type()))
+    }
+
+    pub(crate) fn apply(&self, doc: &mut Value, delta: &Value) -> Result<(), String> {
+        match self.format {
+            DeltaFormat::MergePatch => {
+                patch::merge_patch(doc, delta);
+                Ok(())
+            }
+            DeltaFormat::JsonPatch => patch::json_patch(doc, delta),
+        }
+    }
+
+    pub(crate) fn refresh_url(&self, url: &str) -> Option<String> {
+        let (key, value) = self.full_refresh.as_ref()?;
+        let mut url = reqwest::Url::parse(url).ok()?;
+        url.query_pairs_mut().append_pair(key, value);
+        Some(url.into())
+    }
+}
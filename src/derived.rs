@@ -0,0 +1,162 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Derived {
+    // Change since the previous poll.
+    Delta,
+    // Change since the previous poll per second, e.g. requests/s from a request counter.
+    Rate,
+    // Over the last n polls, including the current one.
+    MovingAverage(usize),
+    Min(usize),
+    Max(usize),
+}
+
+impl Derived {
+    fn window(self) -> usize {
+        match self {
+            Self::Delta | Self::Rate => 2,
+            Self::MovingAverage(n) | Self::Min(n) | Self::Max(n) => n,
+        }
+    }
+
+    fn compute(self, samples: &VecDeque<(Instant, f64)>) -> Option<f64> {
+        let values = samples.iter().map(|(_, value)| *value);
+        match self {
+            Self::Delta | Self::Rate if samples.len() < 2 => None,
+            Self::Delta | Self::Rate => {
+                let (then, previous) = samples.front()?;
+                let (now, current) = samples.back()?;
+                let delta = current - previous;
+                if self == Self::Delta {
+                    return Some(delta);
+                }
+                let secs = now.saturating_duration_since(*then).as_secs_f64();
+                (secs > 0.0).then(|| delta / secs)
+            }
+            Self::MovingAverage(_) => Some(values.sum::<f64>() / samples.len() as f64),
+            Self::Min(_) => values.reduce(f64::min),
+            Self::Max(_) => values.reduce(f64::max),
+        }
+    }
+}
+
+struct Series {
+    name: String,
+    pointer: String,
+    kind: Derived,
+    samples: VecDeque<(Instant, f64)>,
+}
+
+// Derived metrics over numeric JSON pointers, updated on every successful fetch. The latest
+// values are what `PollMeta::derived` reports.
+pub(crate) struct DerivedMetrics<T> {
+    to_value: fn(&T) -> Option<Value>,
+    series: Mutex<Vec<Series>>,
+    latest: Mutex<BTreeMap<String, f64>>,
+}
+
+impl<T> DerivedMetrics<T> {
+    pub(crate) fn new() -> Self
+    where
+        T: serde::Serialize,
+    {
+        Self {
+            to_value: |data| serde_json::to_value(data).ok(),
+            series: Mutex::new(Vec::new()),
+            latest: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn add(&mut self, name: String, pointer: String, kind: Derived) {
+        self.series.get_mut().unwrap().push(Series {
+            name,
+            pointer,
+            kind,
+            samples: VecDeque::with_capacity(kind.window()),
+        });
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        for series in self.series.lock().unwrap().iter() {
+            if !series.pointer.starts_with('/') {
+                return Err(format!("`{}` is not a JSON pointer", series.pointer));
+            }
+            if series.kind.window() == 0 {
+                return Err(format!(
+                    "derived metric `{}` needs a window of at least one poll",
+                    series.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn observe(&self, now: Instant, data: &T) {
+        let Some(value) = (self.to_value)(data) else {
+            return;
+        };
+        let mut latest = BTreeMap::new();
+        for series in self.series.lock().unwrap().iter_mut() {
+            // Missing or non-numeric values are skipped rather than breaking the window.
+            if let Some(sample) = value.pointer(&series.pointer).and_then(Value::as_f64) {
+                if series.samples.len() == series.kind.window() {
+                    series.samples.pop_front();
+                }
+                series.samples.push_back((now, sample));
+            }
+            if let Some(derived) = series.kind.compute(&series.samples) {
+                latest.insert(series.name.clone(), derived);
+            }
+        }
+        *self.latest.lock().unwrap() = latest;
+    }
+
+    pub(crate) fn latest(&self) -> BTreeMap<String, f64> {
+        self.latest.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn test_derived_metrics_over_a_counter() {
+        let mut metrics = DerivedMetrics::<Value>::new();
+        metrics.add("rps".into(), "/requests".into(), Derived::Rate);
+        metrics.add("delta".into(), "/requests".into(), Derived::Delta);
+        metrics.add("avg".into(), "/load".into(), Derived::MovingAverage(2));
+        metrics.add("max".into(), "/load".into(), Derived::Max(3));
+        let start = Instant::now();
+
+        metrics.observe(start, &json!({"requests": 100, "load": 1.0}));
+        assert_eq!(
+            metrics.latest(),
+            BTreeMap::from([("avg".into(), 1.0), ("max".into(), 1.0)])
+        );
+
+        metrics.observe(
+            start + Duration::from_secs(2),
+            &json!({"requests": 150, "load": 3.0}),
+        );
+        metrics.observe(
+            start + Duration::from_secs(4),
+            &json!({"requests": 250, "load": 2.0}),
+        );
+        assert_eq!(
+            metrics.latest(),
+            BTreeMap::from([
+                ("avg".into(), 2.5),
+                ("delta".into(), 100.0),
+                ("max".into(), 3.0),
+                ("rps".into(), 50.0),
+            ])
+        );
+    }
+}
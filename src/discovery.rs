@@ -0,0 +1,132 @@
+use crate::{JsonPoller, JsonPollerBuilder, PollerTask, StartError};
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+type Endpoints<L> = Arc<dyn Fn(&L) -> Vec<String> + Send + Sync>;
+type ChildBuilder<T> = Arc<dyn Fn(&str) -> JsonPollerBuilder<T> + Send + Sync>;
+
+// A meta-poller: `registry` polls a service-discovery document, and every URL that `endpoints`
+// extracts from it gets a child poller of its own. Children are started and stopped as URLs
+// appear in and disappear from the list, and their payloads are delivered tagged with the URL.
+pub struct Discovery<L, T> {
+    registry: JsonPoller<L>,
+    endpoints: Endpoints<L>,
+    child: ChildBuilder<T>,
+    drain: Duration,
+}
+
+impl<L, T> Discovery<L, T>
+where
+    L: DeserializeOwned + Send + 'static,
+    T: DeserializeOwned + Send + 'static,
+{
+    pub fn new<F, B>(registry: JsonPoller<L>, endpoints: F, child: B) -> Self
+    where
+        F: Fn(&L) -> Vec<String> + Send + Sync + 'static,
+        B: Fn(&str) -> JsonPollerBuilder<T> + Send + Sync + 'static,
+    {
+        Self {
+            registry,
+            endpoints: Arc::new(endpoints),
+            child: Arc::new(child),
+            drain: Duration::from_secs(5),
+        }
+    }
+
+    // How long a child whose URL left the list may take to finish its current cycle.
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain = timeout;
+        self
+    }
+
+    // Runs the registry poller on the current task. Children are drained when this returns or
+    // is dropped. A child that cannot be built is retried on the next listing, and logged (with
+    // the registry's `internal_logging`) the first time its URL fails.
+    pub async fn start<F, Fut, E>(&self, on_data: F) -> Result<(), StartError<Infallible>>
+    where
+        F: Fn(&str, T) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: std::fmt::Debug + Send + 'static,
+    {
+        let mut running: BTreeMap<String, PollerTask<E>> = BTreeMap::new();
+        let mut failing = BTreeSet::new();
+        self.registry
+            .start(|listing, _| {
+                let desired = (self.endpoints)(&listing);
+                // Dropping a child drains it; finished ones are restarted below.
+                running.retain(|url, task| desired.contains(url) && !task.is_finished());
+                failing.retain(|url| desired.contains(url));
+                for url in desired {
+                    if running.contains_key(&url) {
+                        continue;
+                    }
+                    match (self.child)(&url).build() {
+                        Ok(poller) => {
+                            let on_data = on_data.clone();
+                            let tag = url.clone();
+                            let task = poller.spawn(move |data, _| on_data(&tag, data));
+                            failing.remove(&url);
+                            running.insert(url, task.drain_on_drop(self.drain));
+                        }
+                        Err(e) => {
+                            if self.registry.log.enabled && !failing.contains(&url) {
+                                tracing::warn!("Failed to build poller for {}: {:?}", url, e);
+                            }
+                            failing.insert(url);
+                        }
+                    }
+                }
+                async { Ok(()) }
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, TestResponse};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_children_follow_the_listing() {
+        let first = serve(|_, _| TestResponse::json("1")).await;
+        let second = serve(|_, _| TestResponse::json("2")).await;
+        let listings = [
+            serde_json::json!([first.url]).to_string(),
+            serde_json::json!([second.url]).to_string(),
+        ];
+        let registry = serve(move |index, _| TestResponse::json(&listings[index.min(1)])).await;
+
+        let discovery = Discovery::new(
+            JsonPoller::<Vec<String>>::builder(&registry.url)
+                .poll_interval_ms(150)
+                .build()
+                .unwrap(),
+            |urls: &Vec<String>| urls.clone(),
+            |url| JsonPoller::<u64>::builder(url).poll_interval_ms(10),
+        )
+        .drain_timeout(Duration::from_millis(10));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        let _ = tokio::time::timeout(
+            Duration::from_millis(400),
+            discovery.start(move |url, value| {
+                recorded.lock().unwrap().push((url.to_string(), value));
+                async { Ok::<(), ()>(()) }
+            }),
+        )
+        .await;
+
+        let seen = seen.lock().unwrap();
+        assert!(seen.contains(&(first.url.clone(), 1)));
+        assert!(seen.contains(&(second.url.clone(), 2)));
+        assert!(seen
+            .iter()
+            .all(|(url, value)| (*url == first.url) == (*value == 1)));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{JsonPoller, Latest};
+use bytes::Bytes;
+use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use http::{Response, StatusCode};
+use serde::Serialize;
+
+// Plain `http` responses, which axum, warp and hyper handlers can all return as they are.
+
+impl<T: Serialize> Latest<T> {
+    // The latest value as JSON, or 503 until the first poll has succeeded and once the value
+    // has outlived its TTL.
+    pub fn to_response(&self) -> Response<Bytes> {
+        if self.get().is_some() && self.latest_if_fresh().is_none() {
+            return json(
+                StatusCode::SERVICE_UNAVAILABLE,
+                br#"{"error":"data is stale"}"#.to_vec(),
+            );
+        }
+        match self.get().map(|value| serde_json::to_vec(&*value)) {
+            Some(Ok(body)) => json(StatusCode::OK, body),
+            Some(Err(e)) => json(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({"error": e.to_string()})
+                    .to_string()
+                    .into(),
+            ),
+            None => json(
+                StatusCode::SERVICE_UNAVAILABLE,
+                br#"{"error":"no data yet"}"#.to_vec(),
+            ),
+        }
+    }
+}
+
+impl<T: Send + 'static> JsonPoller<T> {
+    // Poll state as JSON, for readiness probes. 503 until a poll has succeeded, and again once
+    // the last good poll is older than `stale_after` (or, without it, `value_ttl`). With neither
+    // set, 503 while the latest poll has failed.
+    pub fn health_response(&self) -> Response<Bytes> {
+        let metrics = self.metrics();
+        let since_ok = metrics
+            .last_ok_at
+            .map(|at| at.elapsed().unwrap_or_default());
+        let healthy = match (since_ok, self.stale_after.or(self.value_ttl)) {
+            (None, _) => false,
+            (Some(since_ok), Some(window)) => since_ok <= window,
+            (Some(_), None) => metrics.consecutive_errors == 0,
+        };
+        let body = serde_json::json!({
+            "name": self.name(),
+            "healthy": healthy,
+            "successes": metrics.successes,
+            "errors": metrics.errors,
+            "consecutive_errors": metrics.consecutive_errors,
+            "last_ok_ms_ago": since_ok.map(|since| since.as_millis() as u64),
+            "missed_ticks": metrics.missed_ticks,
+            "bytes_received": metrics.bytes_received,
+            "wire_bytes_received": metrics.wire_bytes_received,
+        });
+        let status = match healthy {
+            true => StatusCode::OK,
+            false => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        json(status, body.to_string().into())
+    }
+}
+
+fn json(status: StatusCode, body: Vec<u8>) -> Response<Bytes> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CACHE_CONTROL, "no-cache")
+        .body(Bytes::from(body))
+        .expect("static response parts are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, TestResponse};
+    use std::time::Duration;
+
+    #[test]
+    fn test_latest_response_waits_for_first_value() {
+        let latest = Latest::new();
+        assert_eq!(
+            latest.to_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        latest.set(vec![1, 2], None);
+        let response = latest.to_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_ref(), b"[1,2]");
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+
+        latest.set(vec![3], Some(std::time::Duration::ZERO));
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        assert_eq!(
+            latest.to_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_follows_recent_polls() {
+        let server = serve(|index, _| match index {
+            0 => TestResponse::json("1"),
+            _ => TestResponse::status(500),
+        })
+        .await;
+        let poll_once = |poller: &JsonPoller<u64>| {
+            let poller = poller.clone();
+            async move {
+                let poll = poller.start(|_, _| async { Ok::<(), ()>(()) });
+                let _ = tokio::time::timeout(Duration::from_millis(20), poll).await;
+            }
+        };
+        let poller = JsonPoller::<u64>::builder(&server.url)
+            .poll_interval_ms(1_000)
+            .build()
+            .unwrap();
+        assert_eq!(
+            poller.health_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        poll_once(&poller).await;
+        assert_eq!(poller.health_response().status(), StatusCode::OK);
+
+        poll_once(&poller).await;
+        let response = poller.health_response();
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["consecutive_errors"], 1);
+        assert_eq!(body["healthy"], false);
+
+        // Within `stale_after`, a failed poll alone doesn't fail the probe.
+        let tolerant = JsonPoller::<u64>::builder(&server.url)
+            .poll_interval_ms(1_000)
+            .stale_after(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        tolerant.metrics.record_ok();
+        poll_once(&tolerant).await;
+        assert_eq!(tolerant.metrics().consecutive_errors, 1);
+        assert_eq!(tolerant.health_response().status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+pub(crate) type Lookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+// Variables are named `{prefix}_{NAME}_{KEY}`, with the poller name upper-cased and anything
+// outside `[A-Z0-9]` replaced by `_`, e.g. `JSON_POLLER_PRICES_INTERVAL_MS`.
+pub(crate) struct EnvOverlay<'a> {
+    prefix: String,
+    lookup: Lookup<'a>,
+}
+
+impl<'a> EnvOverlay<'a> {
+    pub(crate) fn new(prefix: &str, name: &str, lookup: Lookup<'a>) -> Self {
+        let name: String = name
+            .chars()
+            .map(|c| match c.to_ascii_uppercase() {
+                c @ ('A'..='Z' | '0'..='9') => c,
+                _ => '_',
+            })
+            .collect();
+        Self {
+            prefix: format!("{}_{}", prefix.trim_end_matches('_'), name),
+            lookup,
+        }
+    }
+
+    pub(crate) fn get<V: FromStr>(&self, key: &str) -> Result<Option<V>, String> {
+        let var = format!("{}_{}", self.prefix, key);
+        match (self.lookup)(&var) {
+            None => Ok(None),
+            Some(value) => value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid value `{value}` for {var}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variable_names_are_derived_from_prefix_and_name() {
+        let lookup = |var: &str| (var == "JSON_POLLER_EU_PRICES_INTERVAL_MS").then(|| "250".into());
+        let overlay = EnvOverlay::new("JSON_POLLER", "eu-prices", &lookup);
+
+        assert_eq!(overlay.get::<u64>("INTERVAL_MS"), Ok(Some(250)));
+        assert_eq!(overlay.get::<u64>("REQUEST_TIMEOUT_MS"), Ok(None));
+    }
+
+    #[test]
+    fn test_unparsable_value_is_an_error() {
+        let lookup = |_: &str| Some("fast".into());
+        let overlay = EnvOverlay::new("APP_", "prices", &lookup);
+
+        assert_eq!(
+            overlay.get::<u64>("INTERVAL_MS"),
+            Err("invalid value `fast` for APP_PRICES_INTERVAL_MS".into())
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use crate::ApiError;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Why a connection could not be made or was lost, for picking a policy per cause with
+// `on_connect_failure`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectFailure {
+    Resolve,
+    Refused,
+    Reset,
+}
+
+// Cloneable, with the underlying errors shared, so one failure can reach both the handler and
+// the caller, or every caller waiting on a shared `fetch_once`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum JsonPollerError {
+    Connect(Arc<reqwest::Error>),
+    Timeout(Arc<reqwest::Error>),
+    Request(Arc<reqwest::Error>),
+    Status(StatusCode),
+    Decode(Arc<serde_json::Error>),
+    // A compressed body that could not be decompressed.
+    Decompress(Arc<std::io::Error>),
+    Api(ApiError),
+    Delta(String),
+    Integrity(IntegrityError),
+    // A header or query template the `template_context` could not fill in.
+    Template(String),
+}
+
+impl JsonPollerError {
+    pub fn is_connect(&self) -> bool {
+        matches!(self, Self::Connect(_))
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Self::Timeout(_) => true,
+            Self::Connect(e) => e.is_timeout(),
+            _ => false,
+        }
+    }
+
+    // Failures that may go away on their own: connection problems, timeouts, 408, 429 and 5xx.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Connect(_) | Self::Timeout(_) => true,
+            Self::Request(e) => !e.is_builder(),
+            Self::Status(status) => {
+                status.is_server_error()
+                    || *status == StatusCode::REQUEST_TIMEOUT
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Decode(_)
+            | Self::Decompress(_)
+            | Self::Api(_)
+            | Self::Delta(_)
+            | Self::Integrity(_)
+            | Self::Template(_) => false,
+        }
+    }
+
+    // Walks the source chain of transport errors; hyper and the resolver only expose some of
+    // these causes through their messages.
+    pub fn connect_failure(&self) -> Option<ConnectFailure> {
+        let (Self::Connect(e) | Self::Timeout(e) | Self::Request(e)) = self else {
+            return None;
+        };
+        let mut source: Option<&(dyn Error + 'static)> = Some(e.as_ref());
+        while let Some(error) = source {
+            if let Some(io) = error.downcast_ref::<std::io::Error>() {
+                match io.kind() {
+                    std::io::ErrorKind::ConnectionRefused => return Some(ConnectFailure::Refused),
+                    std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe => return Some(ConnectFailure::Reset),
+                    _ => {}
+                }
+            }
+            let message = error.to_string();
+            if message.starts_with("dns error") {
+                return Some(ConnectFailure::Resolve);
+            }
+            if message.starts_with("connection closed before message completed") {
+                return Some(ConnectFailure::Reset);
+            }
+            source = error.source();
+        }
+        None
+    }
+
+    pub(crate) fn decompress(e: std::io::Error) -> Self {
+        Self::Decompress(Arc::new(e))
+    }
+
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::Api(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Status(status) => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JsonPollerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(e) => write!(f, "connection failed: {e}"),
+            Self::Timeout(e) => write!(f, "request timed out: {e}"),
+            Self::Request(e) => write!(f, "request failed: {e}"),
+            Self::Status(status) => write!(f, "HTTP {status}"),
+            Self::Decode(e) => write!(f, "invalid JSON: {e}"),
+            Self::Decompress(e) => write!(f, "invalid compressed body: {e}"),
+            Self::Api(e) => write!(f, "{e}"),
+            Self::Delta(reason) => write!(f, "delta could not be applied: {reason}"),
+            Self::Integrity(e) => write!(f, "payload rejected: {e}"),
+            Self::Template(reason) => write!(f, "request template failed: {reason}"),
+        }
+    }
+}
+
+impl Error for JsonPollerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Connect(e) | Self::Timeout(e) | Self::Request(e) => Some(e.as_ref()),
+            Self::Status(_) | Self::Api(_) | Self::Delta(_) | Self::Template(_) => None,
+            Self::Decode(e) => Some(e.as_ref()),
+            Self::Decompress(e) => Some(e.as_ref()),
+            Self::Integrity(e) => Some(e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for JsonPollerError {
+    fn from(e: reqwest::Error) -> Self {
+        // Connect timeouts report both flags; they belong with the connect failures.
+        if e.is_connect() {
+            Self::Connect(Arc::new(e))
+        } else if e.is_timeout() {
+            Self::Timeout(Arc::new(e))
+        } else {
+            Self::Request(Arc::new(e))
+        }
+    }
+}
+
+// A response that failed `verify_digest` or `verify_signature` (the first three variants name
+// the header), whose `jws_payload` token was rejected, or that could not be decrypted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntegrityError {
+    Missing(String),
+    Malformed(String),
+    Mismatch(String),
+    InvalidToken(String),
+    Undecryptable(String),
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(header) => write!(f, "{header} header is missing"),
+            Self::Malformed(header) => write!(f, "{header} header is malformed"),
+            Self::Mismatch(header) => write!(f, "{header} does not match the body"),
+            Self::InvalidToken(reason) => write!(f, "invalid JWS: {reason}"),
+            Self::Undecryptable(reason) => write!(f, "could not decrypt body: {reason}"),
+        }
+    }
+}
+
+impl Error for IntegrityError {}
+
+impl From<IntegrityError> for JsonPollerError {
+    fn from(e: IntegrityError) -> Self {
+        Self::Integrity(e)
+    }
+}
+
+impl From<serde_json::Error> for JsonPollerError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Decode(Arc::new(e))
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BuildError {
+    Client(reqwest::Error),
+    InvalidConfig(String),
+    Runtime(std::io::Error),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Client(e) => write!(f, "failed to build HTTP client: {e}"),
+            Self::InvalidConfig(reason) => write!(f, "invalid poller configuration: {reason}"),
+            Self::Runtime(e) => write!(f, "failed to start poller runtime: {e}"),
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Client(e) => Some(e),
+            Self::InvalidConfig(_) => None,
+            Self::Runtime(e) => Some(e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for BuildError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Client(e)
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Build(BuildError),
+    DuplicateName(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read config: {e}"),
+            Self::Parse(e) => write!(f, "invalid config: {e}"),
+            Self::Build(e) => write!(f, "{e}"),
+            Self::DuplicateName(name) => write!(f, "duplicate poller name `{name}`"),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Build(e) => Some(e),
+            Self::DuplicateName(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<BuildError> for ConfigError {
+    fn from(e: BuildError) -> Self {
+        Self::Build(e)
+    }
+}
+
+// A failure together with where and when it happened, as fields that log pipelines can index.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PollError {
+    pub poller: String,
+    pub url: String,
+    pub attempt: u32,
+    pub elapsed: Duration,
+    pub status: Option<StatusCode>,
+    // The ID sent with the failed request, with `request_id_header`.
+    pub request_id: Option<String>,
+    pub error: JsonPollerError,
+}
+
+impl PollError {
+    pub(crate) fn new(
+        poller: &str,
+        url: &str,
+        attempt: u32,
+        elapsed: Duration,
+        request_id: Option<String>,
+        error: JsonPollerError,
+    ) -> Self {
+        Self {
+            poller: poller.to_string(),
+            url: url.to_string(),
+            attempt,
+            elapsed,
+            status: error.status(),
+            request_id,
+            error,
+        }
+    }
+
+    pub fn into_inner(self) -> JsonPollerError {
+        self.error
+    }
+}
+
+impl fmt::Display for PollError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (poller `{}`, url {}, attempt {}, after {:?}",
+            self.error,
+            self.poller,
+            redacted(&self.url),
+            self.attempt,
+            self.elapsed
+        )?;
+        match &self.request_id {
+            Some(id) => write!(f, ", request {id})"),
+            None => f.write_str(")"),
+        }
+    }
+}
+
+// Drops the password and masks the username, so the URL can go into logs.
+fn redacted(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut url) if !url.username().is_empty() || url.password().is_some() => {
+            let _ = url.set_password(None);
+            let _ = url.set_username("redacted");
+            url.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+impl Error for PollError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AvailabilityError {
+    // No poll succeeded within the deadline; `last` is the most recent failure, if any.
+    TimedOut {
+        deadline: Duration,
+        polls: u64,
+        last: Option<PollError>,
+    },
+    // `fetch_with_retries` used up its attempts.
+    Exhausted {
+        attempts: u32,
+        last: PollError,
+    },
+    Stopped(PollError),
+}
+
+impl fmt::Display for AvailabilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut {
+                deadline,
+                polls,
+                last: Some(last),
+            } => write!(
+                f,
+                "not available after {deadline:?} ({polls} poll(s)), last error: {last}"
+            ),
+            Self::TimedOut {
+                deadline, polls, ..
+            } => write!(f, "not available after {deadline:?} ({polls} poll(s))"),
+            Self::Exhausted { attempts, last } => {
+                write!(f, "still failing after {attempts} attempt(s): {last}")
+            }
+            Self::Stopped(e) => write!(f, "polling stopped: {e}"),
+        }
+    }
+}
+
+impl Error for AvailabilityError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::TimedOut { last, .. } => last.as_ref().map(|e| e as _),
+            Self::Exhausted { last: e, .. } | Self::Stopped(e) => Some(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StartError<E> {
+    Handler(E),
+    Stopped(PollError),
+}
+
+impl<E: fmt::Display> fmt::Display for StartError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Handler(e) => write!(f, "handler failed: {e}"),
+            Self::Stopped(e) => write!(f, "polling stopped: {e}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for StartError<E> {}
@@ -0,0 +1,100 @@
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PollEvent {
+    MissedTicks {
+        missed: u64,
+        total: u64,
+    },
+    ClockGapDetected {
+        gap: Duration,
+    },
+    Stale {
+        age: Duration,
+    },
+    DataStale {
+        age: Duration,
+    },
+    Empty {
+        status: StatusCode,
+    },
+    BandwidthThrottled {
+        delay: Duration,
+    },
+    RateLimitThrottled {
+        remaining: u64,
+        delay: Duration,
+    },
+    Recovered {
+        outage: Duration,
+        failures: u32,
+    },
+    // `observed` is the `quantile` latency of the SLO window when it crossed `threshold`.
+    LatencySloBreached {
+        quantile: f64,
+        observed: Duration,
+        threshold: Duration,
+    },
+    LatencySloRecovered {
+        quantile: f64,
+        observed: Duration,
+        threshold: Duration,
+    },
+    Unchanged,
+    UrlRefreshed,
+    ResumeTokenExpired,
+    // `start_acked` delivered a payload whose `Ack` was dropped; it will be fetched again.
+    Unacknowledged {
+        tick: u64,
+    },
+    // The full `delivery_queue` discarded poll `tick` while queueing poll `queued_by`; the two
+    // are the same with `Overflow::DropNewest`.
+    DeliveryDropped {
+        tick: u64,
+        queued_by: u64,
+    },
+    DeltaRejected {
+        reason: String,
+    },
+    Suspended {
+        until_active: Duration,
+    },
+    CertificateExpiring {
+        issuer: String,
+        expires_in: Duration,
+    },
+    HostChanged {
+        configured: String,
+        landed: String,
+    },
+    // JSON pointers of the payload fields `T` skipped, reported when the set changes.
+    UnknownFields {
+        fields: Vec<String>,
+    },
+    Paused,
+    Resumed,
+    Heartbeat {
+        tick: u64,
+        scheduled: Instant,
+        started: Instant,
+        outcome: PollOutcome,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollOutcome {
+    Success,
+    Empty,
+    Unchanged,
+    Failed,
+    // Scheduled under `dry_run`, without a fetch.
+    DryRun,
+}
+
+pub(crate) type EventHandler = Arc<dyn Fn(&str, &PollEvent) + Send + Sync>;
+pub(crate) type StaleHandler = Arc<dyn Fn(Duration) + Send + Sync>;
+pub(crate) type EmptyHandler = Arc<dyn Fn(StatusCode) + Send + Sync>;
+pub(crate) type RecoveredHandler = Arc<dyn Fn(Duration, u32) + Send + Sync>;
@@ -0,0 +1,158 @@
+use crate::client::{ClientConfig, SharedClient};
+use crate::net::{HttpVersion, IpFamily};
+use crate::{JsonPollerBuilder, Payload};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Network options configured once and shared by every poller it mints: they all go through one
+// connection pool. A minted builder that changes any of these options gets a client of its own.
+#[derive(Clone, Default)]
+pub struct PollerFactory {
+    client: ClientConfig,
+    shared: Arc<SharedClient>,
+}
+
+impl PollerFactory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn builder<T: Payload + 'static>(&self, url: impl Into<String>) -> JsonPollerBuilder<T> {
+        JsonPollerBuilder::new(url).shared_client(self.client.clone(), self.shared.clone())
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.client.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.client.pool_max_idle_per_host = max;
+        self
+    }
+
+    pub fn pool_idle_timeout_secs(mut self, secs: u64) -> Self {
+        self.client.pool_idle_timeout_secs = secs;
+        self
+    }
+
+    pub fn request_timeout_ms(mut self, ms: u64) -> Self {
+        self.client.request_timeout_ms = ms;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.client.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn local_address(mut self, addr: IpAddr) -> Self {
+        self.client.local_address = Some(addr);
+        self
+    }
+
+    pub fn ip_family(mut self, family: IpFamily) -> Self {
+        self.client.ip_family = family;
+        self
+    }
+
+    pub fn http_version(mut self, version: HttpVersion) -> Self {
+        self.client.http_version = version;
+        self
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "illumos",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "solaris",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "watchos",
+    ))]
+    pub fn interface(mut self, interface: impl Into<String>) -> Self {
+        self.client.interface = Some(interface.into());
+        self
+    }
+
+    pub fn tcp_keepalive_secs(mut self, secs: u64) -> Self {
+        self.client.tcp_keepalive_secs = secs;
+        self
+    }
+
+    pub fn tcp_keepalive_interval_secs(mut self, secs: u64) -> Self {
+        self.client.tcp_keepalive_interval_secs = Some(secs);
+        self
+    }
+
+    pub fn tcp_keepalive_retries(mut self, retries: u32) -> Self {
+        self.client.tcp_keepalive_retries = Some(retries);
+        self
+    }
+
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.client.tcp_nodelay = enabled;
+        self
+    }
+
+    pub fn max_connection_age(mut self, age: Duration) -> Self {
+        self.client.max_connection_age = Some(age);
+        self
+    }
+
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.client.gzip = Some(enable);
+        self
+    }
+
+    #[cfg(feature = "brotli")]
+    pub fn brotli(mut self, enable: bool) -> Self {
+        self.client.brotli = Some(enable);
+        self
+    }
+
+    #[cfg(feature = "zstd")]
+    pub fn zstd(mut self, enable: bool) -> Self {
+        self.client.zstd = Some(enable);
+        self
+    }
+
+    #[cfg(feature = "deflate")]
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.client.deflate = Some(enable);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minted_pollers_share_a_client_unless_overridden() {
+        let factory = PollerFactory::new().request_timeout_ms(2_000);
+        let prices = factory
+            .builder::<u64>("http://example.com/prices")
+            .build()
+            .unwrap();
+        let rates = factory
+            .builder::<String>("http://example.com/rates")
+            .poll_interval_ms(5_000)
+            .build()
+            .unwrap();
+        let slow = factory
+            .builder::<u64>("http://example.com/slow")
+            .request_timeout_ms(30_000)
+            .build()
+            .unwrap();
+
+        assert!(factory.shared.same(&prices.client));
+        assert!(factory.shared.same(&rates.client));
+        assert!(!factory.shared.same(&slow.client));
+    }
+}
@@ -0,0 +1,116 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+trait Envelope: Any + fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<E: Any + fmt::Debug + Send + Sync> Envelope for E {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+type EnvelopeFn = Arc<dyn Fn(&[u8]) -> Option<Arc<dyn Envelope>> + Send + Sync>;
+
+// A response that did not match `T` but did match the configured error envelope, or any JSON at
+// all when falling back to `Value`.
+#[derive(Clone)]
+pub struct ApiError {
+    body: Value,
+    envelope: Option<Arc<dyn Envelope>>,
+}
+
+impl ApiError {
+    pub fn body(&self) -> &Value {
+        &self.body
+    }
+
+    pub fn envelope<E: Any>(&self) -> Option<&E> {
+        self.envelope.as_deref()?.as_any().downcast_ref()
+    }
+}
+
+impl fmt::Debug for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.envelope {
+            Some(envelope) => f.debug_tuple("ApiError").field(envelope).finish(),
+            None => f.debug_tuple("ApiError").field(&self.body).finish(),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API returned an error payload: {}", self.body)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct DecodeFallback {
+    envelope: Option<EnvelopeFn>,
+    value: bool,
+}
+
+impl DecodeFallback {
+    pub(crate) fn set_envelope<E>(&mut self)
+    where
+        E: DeserializeOwned + fmt::Debug + Send + Sync + 'static,
+    {
+        let parse = |body: &[u8]| {
+            let envelope: E = serde_json::from_slice(body).ok()?;
+            Some(Arc::new(envelope) as Arc<dyn Envelope>)
+        };
+        self.envelope = Some(Arc::new(parse));
+    }
+
+    pub(crate) fn set_value(&mut self, enabled: bool) {
+        self.value = enabled;
+    }
+
+    // Tries the envelope, then plain JSON. Only runs once `T` has already failed to parse.
+    pub(crate) fn classify(&self, body: &[u8]) -> Option<ApiError> {
+        if self.envelope.is_none() && !self.value {
+            return None;
+        }
+        let envelope = self.envelope.as_ref().and_then(|parse| parse(body));
+        if envelope.is_none() && !self.value {
+            return None;
+        }
+        let body = serde_json::from_slice(body).ok()?;
+        Some(ApiError { body, envelope })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Envelope {
+        code: u32,
+        message: String,
+    }
+
+    #[test]
+    fn test_envelope_is_tried_before_value() {
+        let mut fallback = DecodeFallback::default();
+        fallback.set_envelope::<Envelope>();
+
+        let error = fallback
+            .classify(br#"{"code": 7, "message": "maintenance"}"#)
+            .unwrap();
+        assert_eq!(error.envelope::<Envelope>().unwrap().code, 7);
+        assert_eq!(error.body()["message"], "maintenance");
+
+        assert!(fallback.classify(br#"{"unexpected": true}"#).is_none());
+        fallback.set_value(true);
+        let error = fallback.classify(br#"{"unexpected": true}"#).unwrap();
+        assert!(error.envelope::<Envelope>().is_none());
+        assert!(fallback.classify(b"not json").is_none());
+    }
+}
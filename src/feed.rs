@@ -0,0 +1,13 @@
+use std::fmt::Debug;
+
+// A message of a feed that interleaves full snapshots with deltas, typically an enum
+// dispatched on a discriminator with `#[serde(tag = "type")]`. `start_feed` keeps the state
+// and delivers it after every message.
+pub trait FeedMessage: Sized {
+    type State: Clone + Send;
+    type Error: Debug;
+
+    // A snapshot replaces `state`; a delta updates it in place. An error, such as a delta
+    // arriving before any snapshot, discards the state until the next snapshot.
+    fn apply(self, state: &mut Option<Self::State>) -> Result<(), Self::Error>;
+}
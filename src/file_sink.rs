@@ -0,0 +1,171 @@
+use crate::sink::PollSink;
+use crate::PollMeta;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+struct Segment {
+    file: File,
+    opened: SystemTime,
+    bytes: u64,
+}
+
+// Appends each payload as one NDJSON line (`{"at": <unix ms>, "tick": n, "payload": ...}`) to
+// `{prefix}-{unix ms}.ndjson` files in `dir`, starting a new file once the current one exceeds
+// `max_bytes` or `max_age`. Writes run on the blocking pool.
+pub struct FileSink {
+    dir: PathBuf,
+    prefix: String,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    only_changes: bool,
+    last: Option<Vec<u8>>,
+    segment: Option<Segment>,
+}
+
+impl FileSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: "poll".into(),
+            max_bytes: None,
+            max_age: None,
+            only_changes: false,
+            last: None,
+            segment: None,
+        }
+    }
+
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    // Skip payloads that serialize identically to the previous one.
+    pub fn only_changes(mut self, only_changes: bool) -> Self {
+        self.only_changes = only_changes;
+        self
+    }
+
+    fn needs_rotation(&self, segment: &Segment, now: SystemTime) -> bool {
+        let too_big = self.max_bytes.is_some_and(|max| segment.bytes >= max);
+        let too_old = self.max_age.is_some_and(|max| {
+            now.duration_since(segment.opened)
+                .is_ok_and(|age| age >= max)
+        });
+        too_big || too_old
+    }
+
+    fn write(&mut self, line: &[u8], now: SystemTime) -> io::Result<()> {
+        if let Some(segment) = self.segment.take().filter(|s| !self.needs_rotation(s, now)) {
+            self.segment = Some(segment);
+        }
+        let segment = match &mut self.segment {
+            Some(segment) => segment,
+            None => {
+                std::fs::create_dir_all(&self.dir)?;
+                let path = self
+                    .dir
+                    .join(format!("{}-{}.ndjson", self.prefix, unix_millis(now)));
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                let bytes = file.metadata()?.len();
+                self.segment.insert(Segment {
+                    file,
+                    opened: now,
+                    bytes,
+                })
+            }
+        };
+        segment.file.write_all(line)?;
+        segment.bytes += line.len() as u64;
+        Ok(())
+    }
+}
+
+impl<T: Serialize + Send> PollSink<T> for FileSink {
+    type Error = io::Error;
+
+    async fn deliver(&mut self, item: T, meta: PollMeta) -> Result<(), Self::Error> {
+        let payload = serde_json::to_vec(&item)?;
+        if self.only_changes && self.last.as_deref() == Some(payload.as_slice()) {
+            return Ok(());
+        }
+        let now = SystemTime::now();
+        let mut line = format!(
+            r#"{{"at":{},"tick":{},"payload":"#,
+            unix_millis(now),
+            meta.tick
+        )
+        .into_bytes();
+        line.extend_from_slice(&payload);
+        line.extend_from_slice(b"}\n");
+
+        // The sink travels to the blocking pool for the write and comes back afterwards.
+        let mut sink = std::mem::replace(self, FileSink::new(PathBuf::new()));
+        let (sink, result) = tokio::task::spawn_blocking(move || {
+            let result = sink.write(&line, now);
+            (sink, result)
+        })
+        .await
+        .expect("file sink write panicked");
+        *self = sink;
+        result?;
+        if self.only_changes {
+            self.last = Some(payload);
+        }
+        Ok(())
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rotates_and_skips_unchanged_payloads() {
+        let dir = std::env::temp_dir().join(format!("json-poller-sink-{}", std::process::id()));
+        let mut sink = FileSink::new(&dir).max_bytes(1).only_changes(true);
+
+        sink.deliver(serde_json::json!({"a": 1}), PollMeta::new(1))
+            .await
+            .unwrap();
+        sink.deliver(serde_json::json!({"a": 1}), PollMeta::new(2))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        sink.deliver(serde_json::json!({"a": 2}), PollMeta::new(3))
+            .await
+            .unwrap();
+
+        let mut lines = Vec::new();
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let text = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert_eq!(text.lines().count(), 1);
+            lines.push(serde_json::from_str::<serde_json::Value>(text.trim()).unwrap());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        lines.sort_by_key(|line| line["tick"].as_u64());
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["payload"], serde_json::json!({"a": 1}));
+        assert_eq!(lines[1]["tick"], 3);
+    }
+}
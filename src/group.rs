@@ -0,0 +1,367 @@
+use crate::{ConfigError, JsonPoller, PollControl, PollerTask, StartError};
+use futures_util::future::join_all;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinError;
+
+// How a group spreads the first polls of pollers that share an interval, so the target does not
+// receive all of their requests at the same moment every cycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Stagger {
+    // Every poller starts right away.
+    Disabled,
+    // First polls are spread evenly across the shared interval.
+    #[default]
+    Interval,
+    // First polls are spread evenly across at most this window.
+    Window(Duration),
+}
+
+// The first-poll delay of each poller, in order: pollers sharing an interval are spread evenly
+// across the window `stagger` allows for it.
+pub(crate) fn stagger_offsets<'a, T: Send + 'static>(
+    stagger: Stagger,
+    pollers: impl Iterator<Item = &'a JsonPoller<T>> + Clone,
+) -> Vec<Duration> {
+    let mut peers: BTreeMap<Duration, u32> = BTreeMap::new();
+    for poller in pollers.clone() {
+        *peers.entry(poller.poll_interval()).or_default() += 1;
+    }
+    let mut seen: BTreeMap<Duration, u32> = BTreeMap::new();
+    pollers
+        .map(|poller| {
+            let interval = poller.poll_interval();
+            let index = seen.entry(interval).or_default();
+            let window = match stagger {
+                Stagger::Disabled => Duration::ZERO,
+                Stagger::Interval => interval,
+                Stagger::Window(window) => window.min(interval),
+            };
+            let offset = window * *index / peers[&interval];
+            *index += 1;
+            offset
+        })
+        .collect()
+}
+
+// A named set of pollers that share one callback and are started and stopped together.
+pub struct PollerGroup<T> {
+    pollers: BTreeMap<String, JsonPoller<T>>,
+    stagger: Stagger,
+}
+
+impl<T> Default for PollerGroup<T> {
+    fn default() -> Self {
+        Self {
+            pollers: BTreeMap::new(),
+            stagger: Stagger::default(),
+        }
+    }
+}
+
+impl<T> PollerGroup<T>
+where
+    T: Send + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, poller: JsonPoller<T>) -> Result<(), ConfigError> {
+        let name = poller.name().to_string();
+        if self.pollers.contains_key(&name) {
+            return Err(ConfigError::DuplicateName(name));
+        }
+        self.pollers.insert(name, poller);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<JsonPoller<T>> {
+        self.pollers.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&JsonPoller<T>> {
+        self.pollers.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.pollers.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pollers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pollers.is_empty()
+    }
+
+    pub fn set_stagger(&mut self, stagger: Stagger) {
+        self.stagger = stagger;
+    }
+
+    // Each poller with the delay before its first poll. The offsets add to any `initial_delay`
+    // the poller was built with.
+    fn staggered(&self) -> impl Iterator<Item = (&String, JsonPoller<T>)> + '_ {
+        let offsets = stagger_offsets(self.stagger, self.pollers.values());
+        self.pollers
+            .iter()
+            .zip(offsets)
+            .map(|((name, poller), offset)| (name, poller.delayed(offset)))
+    }
+
+    // Pausing takes effect once each poller's cycle in flight has finished, and carries over to
+    // tasks already spawned from the group.
+    pub fn pause_all(&self) {
+        self.controls(None).for_each(PollControl::pause);
+    }
+
+    pub fn resume_all(&self) {
+        self.controls(None).for_each(PollControl::resume);
+    }
+
+    pub fn trigger_all_now(&self) {
+        self.controls(None).for_each(PollControl::trigger_now);
+    }
+
+    pub fn pause_labeled(&self, label: &str) {
+        self.controls(Some(label)).for_each(PollControl::pause);
+    }
+
+    pub fn resume_labeled(&self, label: &str) {
+        self.controls(Some(label)).for_each(PollControl::resume);
+    }
+
+    pub fn trigger_labeled_now(&self, label: &str) {
+        self.controls(Some(label))
+            .for_each(PollControl::trigger_now);
+    }
+
+    fn controls<'a>(&'a self, label: Option<&'a str>) -> impl Iterator<Item = &'a PollControl> {
+        self.pollers
+            .values()
+            .filter(move |poller| label.is_none_or(|label| poller.labels().any(|l| l == label)))
+            .map(JsonPoller::control)
+    }
+
+    pub fn spawn<F, Fut, E>(&self, on_data: F) -> GroupTask<E>
+    where
+        T: DeserializeOwned,
+        F: Fn(&str, T) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: std::fmt::Debug + Send + 'static,
+    {
+        let tasks = self
+            .staggered()
+            .map(|(name, poller)| {
+                let on_data = on_data.clone();
+                let owned = name.clone();
+                let task = poller.spawn(move |data, _| on_data(&owned, data));
+                (name.clone(), task)
+            })
+            .collect();
+        GroupTask { tasks }
+    }
+}
+
+impl<T> PollerGroup<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    // Runs every poller concurrently on the current task until `until` resolves, then waits for
+    // their in-flight cycles. Unlike `spawn`, nothing outlives the call.
+    pub async fn run_until<F, Fut, E>(
+        &self,
+        on_data: F,
+        until: impl Future<Output = ()>,
+    ) -> Vec<(String, Result<(), StartError<E>>)>
+    where
+        F: Fn(&str, T) -> Fut + Sync,
+        Fut: Future<Output = Result<(), E>> + Send,
+        E: std::fmt::Debug,
+    {
+        let (stop, signal) = watch::channel(false);
+        let on_data = &on_data;
+        let runs = self.staggered().map(|(name, poller)| {
+            let signal = signal.clone();
+            async move {
+                let result = poller
+                    .start_until(|data, _| on_data(name, data), Some(signal))
+                    .await;
+                (name.clone(), result)
+            }
+        });
+        let all = join_all(runs);
+        tokio::pin!(all);
+        tokio::select! {
+            results = &mut all => return results,
+            _ = until => {}
+        }
+        stop.send_replace(true);
+        all.await
+    }
+}
+
+type TaskResult<E> = Result<Result<(), StartError<E>>, JoinError>;
+
+pub struct GroupTask<E> {
+    tasks: Vec<(String, PollerTask<E>)>,
+}
+
+impl<E> GroupTask<E> {
+    pub fn abort_on_drop(self) -> Self {
+        let tasks = self
+            .tasks
+            .into_iter()
+            .map(|(name, task)| (name, task.abort_on_drop()))
+            .collect();
+        Self { tasks }
+    }
+
+    pub fn abort(&self) {
+        for (_, task) in &self.tasks {
+            task.abort();
+        }
+    }
+
+    pub async fn shutdown(self, timeout: Duration) -> Vec<(String, TaskResult<E>)> {
+        let shutdowns = self.tasks.into_iter().map(|(name, task)| async move {
+            let result = task.shutdown(timeout).await;
+            (name, result)
+        });
+        join_all(shutdowns).await
+    }
+
+    pub async fn join(self) -> Vec<(String, TaskResult<E>)> {
+        let joins = self
+            .tasks
+            .into_iter()
+            .map(|(name, task)| async move { (name, task.await) });
+        join_all(joins).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, TestResponse};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_run_until_stops_every_poller() {
+        let a = serve(|_, _| TestResponse::json("1")).await;
+        let b = serve(|_, _| TestResponse::json("2")).await;
+        let mut group = PollerGroup::new();
+        for (name, url) in [("a", &a.url), ("b", &b.url)] {
+            let poller = JsonPoller::<u64>::builder(url)
+                .name(name)
+                .poll_interval_ms(10)
+                .build()
+                .unwrap();
+            group.insert(poller).unwrap();
+        }
+
+        let seen = Mutex::new(Vec::new());
+        let results = group
+            .run_until(
+                |name, value| {
+                    seen.lock().unwrap().push((name.to_string(), value));
+                    async { Ok::<(), ()>(()) }
+                },
+                tokio::time::sleep(Duration::from_millis(35)),
+            )
+            .await;
+
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        let hits = (a.hits(), b.hits());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!((a.hits(), b.hits()), hits);
+        assert!(seen.lock().unwrap().contains(&("b".to_string(), 2)));
+    }
+
+    #[tokio::test]
+    async fn test_pause_by_label_stops_only_tagged_pollers() {
+        let a = serve(|_, _| TestResponse::json("1")).await;
+        let b = serve(|_, _| TestResponse::json("2")).await;
+        let mut group = PollerGroup::new();
+        for (name, url, label) in [("a", &a.url, "analytics"), ("b", &b.url, "pricing")] {
+            let poller = JsonPoller::<u64>::builder(url)
+                .name(name)
+                .label(label)
+                .poll_interval_ms(20)
+                .build()
+                .unwrap();
+            group.insert(poller).unwrap();
+        }
+        let task = group.spawn(|_, _| async { Ok::<(), ()>(()) });
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        group.pause_labeled("analytics");
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let hits = (a.hits(), b.hits());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(a.hits(), hits.0);
+        assert!(b.hits() > hits.1);
+
+        group.pause_all();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let paused = b.hits();
+        group.trigger_all_now();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!((a.hits(), b.hits()), (hits.0, paused));
+
+        group.resume_all();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(a.hits() > hits.0);
+        task.shutdown(Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn test_trigger_polls_before_interval() {
+        let server = serve(|_, _| TestResponse::json("1")).await;
+        let mut group = PollerGroup::new();
+        group
+            .insert(
+                JsonPoller::<u64>::builder(&server.url)
+                    .poll_interval_ms(60_000)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let task = group.spawn(|_, _| async { Ok::<(), ()>(()) });
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(server.hits(), 1);
+
+        group.trigger_all_now();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(server.hits(), 2);
+        task.shutdown(Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn test_first_polls_are_spread_across_interval() {
+        for (stagger, early, later) in [(Stagger::Interval, 1, 3), (Stagger::Disabled, 4, 4)] {
+            let server = serve(|_, _| TestResponse::json("1")).await;
+            let mut group = PollerGroup::new();
+            group.set_stagger(stagger);
+            for name in ["a", "b", "c", "d"] {
+                let poller = JsonPoller::<u64>::builder(&server.url)
+                    .name(name)
+                    .poll_interval_ms(400)
+                    .build()
+                    .unwrap();
+                group.insert(poller).unwrap();
+            }
+            let task = group.spawn(|_, _| async { Ok::<(), ()>(()) });
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(server.hits(), early);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            assert_eq!(server.hits(), later);
+            task.shutdown(Duration::from_secs(1)).await;
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{PollError, PollMeta};
+use std::future::Future;
+
+// A stateful alternative to the `start` closures. Only `on_data` is required; errors and
+// unchanged polls (see `head_precheck`) are ignored unless overridden.
+pub trait PollHandler<T>: Send {
+    type Error: std::fmt::Debug;
+
+    fn on_data(
+        &mut self,
+        data: T,
+        meta: PollMeta,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    fn on_error(
+        &mut self,
+        _error: PollError,
+        _meta: PollMeta,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+
+    fn on_unchanged(
+        &mut self,
+        _meta: PollMeta,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        async { Ok(()) }
+    }
+}
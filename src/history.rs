@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HistoryEntry<T> {
+    pub at: SystemTime,
+    pub value: T,
+}
+
+// The last `capacity` payloads, oldest first. `clone` is captured where `T: Clone` is known so
+// the fetch path does not need the bound.
+pub(crate) struct History<T> {
+    capacity: usize,
+    entries: Mutex<VecDeque<HistoryEntry<T>>>,
+    clone: fn(&T) -> T,
+}
+
+impl<T> History<T> {
+    pub(crate) fn new(capacity: usize) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            clone: T::clone,
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn record(&self, at: SystemTime, value: &T) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(HistoryEntry {
+            at,
+            value: (self.clone)(value),
+        });
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<HistoryEntry<T>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .map(|entry| HistoryEntry {
+                at: entry.at,
+                value: (self.clone)(&entry.value),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_keeps_the_most_recent_entries() {
+        let history = History::new(2);
+        let start = SystemTime::UNIX_EPOCH;
+        for i in 0..3u64 {
+            history.record(start + Duration::from_secs(i), &i);
+        }
+
+        let entries = history.snapshot();
+        assert_eq!(entries.iter().map(|e| e.value).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(entries[0].at, start + Duration::from_secs(1));
+    }
+}
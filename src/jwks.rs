@@ -0,0 +1,252 @@
+use crate::jws::Header;
+use crate::{base64, IntegrityError, JsonPoller, PollMeta, PollerTask, SignatureKey};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+// A token whose `kid` is unknown triggers at most one early refresh per this window, so tokens
+// with made-up key ids can't turn into a request per poll against the JWKS endpoint.
+const KID_MISS_COOLDOWN: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: Option<String>,
+    alg: Option<String>,
+    #[serde(rename = "use")]
+    use_: Option<String>,
+    crv: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+impl Jwk {
+    // Public keys only: a JWKS document is readable by anyone, so a shared `oct` secret published
+    // in one would let any reader forge tokens. Keys marked for another `use` or `alg` are skipped.
+    fn key(&self) -> Option<SignatureKey> {
+        if self.use_.as_deref().is_some_and(|use_| use_ != "sig") {
+            return None;
+        }
+        let decode = |part: &Option<String>| base64::decode(part.as_deref()?);
+        let key = match (self.kty.as_str(), self.crv.as_deref()) {
+            ("RSA", _) => {
+                SignatureKey::RsaPkcs1Sha256(rsa_public_key(&decode(&self.n)?, &decode(&self.e)?))
+            }
+            ("EC", Some("P-256")) => {
+                let mut point = vec![0x04];
+                point.extend(decode(&self.x)?);
+                point.extend(decode(&self.y)?);
+                SignatureKey::EcdsaP256Sha256(point)
+            }
+            ("OKP", Some("Ed25519")) => SignatureKey::Ed25519(decode(&self.x)?),
+            _ => return None,
+        };
+        match self.alg.as_deref() {
+            Some(alg) if alg != key.jws_alg() => None,
+            _ => Some(key),
+        }
+    }
+}
+
+// DER `RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }`.
+fn rsa_public_key(n: &[u8], e: &[u8]) -> Vec<u8> {
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        let len = content.len().to_be_bytes();
+        let len = &len[len.iter().position(|&b| b != 0).unwrap_or(len.len() - 1)..];
+        match content.len() {
+            0..=0x7f => out.push(content.len() as u8),
+            _ => {
+                out.push(0x80 | len.len() as u8);
+                out.extend_from_slice(len);
+            }
+        }
+        out.extend_from_slice(content);
+        out
+    }
+    let integer = |bytes: &[u8]| {
+        let bytes = &bytes[bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len())..];
+        match bytes.first() {
+            Some(&first) if first & 0x80 == 0 => tlv(0x02, bytes),
+            _ => tlv(0x02, &[&[0][..], bytes].concat()),
+        }
+    };
+    tlv(0x30, &[integer(n), integer(e)].concat())
+}
+
+type Keys = Option<Vec<(Option<String>, SignatureKey)>>;
+
+// Verification keys from a JWKS endpoint, kept up to date by a `JsonPoller` of its own. It is
+// started on first use and stops with the last clone of the poller that owns it.
+pub(crate) struct JwksKeys {
+    poller: JsonPoller<JwkSet>,
+    wait: Duration,
+    running: OnceLock<Running>,
+}
+
+struct Running {
+    keys: watch::Receiver<Keys>,
+    last_miss: Mutex<Option<Instant>>,
+    _task: PollerTask<Infallible>,
+}
+
+impl JwksKeys {
+    pub(crate) fn new(poller: JsonPoller<JwkSet>, wait: Duration) -> Self {
+        Self {
+            poller,
+            wait,
+            running: OnceLock::new(),
+        }
+    }
+
+    fn running(&self) -> &Running {
+        self.running.get_or_init(|| {
+            let (sender, keys) = watch::channel(None);
+            let poller = self.poller.clone();
+            let (shutdown, signal) = watch::channel(false);
+            let handle = tokio::spawn(async move {
+                let on_data = |set: JwkSet, _meta: PollMeta| {
+                    sender.send_replace(usable(&set));
+                    async { Ok(()) }
+                };
+                poller.deliver_until(on_data, Some(signal)).await
+            });
+            Running {
+                keys,
+                last_miss: Mutex::new(None),
+                _task: PollerTask::new(handle, shutdown).abort_on_drop(),
+            }
+        })
+    }
+
+    // Finds the key for a token, waiting for the first key set and refreshing it early when the
+    // token names a `kid` that isn't in it.
+    pub(crate) async fn key_for(&self, header: &Header) -> Result<SignatureKey, IntegrityError> {
+        let running = self.running();
+        let mut keys = running.keys.clone();
+        let unavailable = || IntegrityError::InvalidToken("JWKS keys are unavailable".into());
+        let loaded = tokio::time::timeout(self.wait, keys.wait_for(Option::is_some))
+            .await
+            .is_ok_and(|loaded| loaded.is_ok());
+        if !loaded {
+            return Err(unavailable());
+        }
+        if let Some(key) = find(&keys.borrow_and_update(), header) {
+            return Ok(key);
+        }
+        let refresh = {
+            let mut last_miss = running.last_miss.lock().unwrap();
+            let due = last_miss.is_none_or(|at| at.elapsed() >= KID_MISS_COOLDOWN);
+            if due {
+                *last_miss = Some(Instant::now());
+            }
+            due
+        };
+        if refresh {
+            self.poller.control().trigger_now();
+            let _ = tokio::time::timeout(self.wait, keys.changed()).await;
+            if let Some(key) = find(&keys.borrow(), header) {
+                return Ok(key);
+            }
+        }
+        Err(IntegrityError::InvalidToken(format!(
+            "no JWKS key for kid {}",
+            header.kid.as_deref().unwrap_or("(none)")
+        )))
+    }
+}
+
+fn usable(set: &JwkSet) -> Keys {
+    let keys = set.keys.iter();
+    Some(
+        keys.filter_map(|jwk| Some((jwk.kid.clone(), jwk.key()?)))
+            .collect(),
+    )
+}
+
+fn find(keys: &Keys, header: &Header) -> Option<SignatureKey> {
+    keys.iter()
+        .flatten()
+        .filter(|(kid, _)| header.kid.is_none() || *kid == header.kid)
+        .map(|(_, key)| key)
+        .find(|key| key.jws_alg() == header.alg)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rsa_components_become_der() {
+        // Leading zeros are stripped; a set high bit gets a zero byte so the INTEGER stays
+        // positive.
+        assert_eq!(
+            rsa_public_key(&[0x00, 0x80, 0x01], &[0x01, 0x00, 0x01]),
+            [0x30, 0x0a, 0x02, 0x03, 0x00, 0x80, 0x01, 0x02, 0x03, 0x01, 0x00, 0x01]
+        );
+        let modulus = vec![0xc1; 256];
+        let der = rsa_public_key(&modulus, &[0x01, 0x00, 0x01]);
+        assert_eq!(&der[..4], &[0x30, 0x82, 0x01, 0x0a]);
+    }
+
+    #[test]
+    fn test_key_is_matched_by_kid_and_alg() {
+        let set: JwkSet = serde_json::from_str(
+            r#"{"keys": [
+                {"kty": "OKP", "crv": "Ed25519", "kid": "old", "x": "b2xk"},
+                {"kty": "OKP", "crv": "Ed25519", "kid": "new", "x": "bmV3"},
+                {"kty": "EC", "crv": "P-384", "kid": "new", "x": "AA", "y": "AA"}
+            ]}"#,
+        )
+        .unwrap();
+        let keys = usable(&set);
+        assert_eq!(keys.as_ref().unwrap().len(), 2);
+        let header = |alg: &str, kid: Option<&str>| Header {
+            alg: alg.into(),
+            kid: kid.map(Into::into),
+        };
+
+        let new = find(&keys, &header("EdDSA", Some("new")));
+        assert!(matches!(new, Some(SignatureKey::Ed25519(k)) if k == b"new"));
+        let any = find(&keys, &header("EdDSA", None));
+        assert!(matches!(any, Some(SignatureKey::Ed25519(k)) if k == b"old"));
+        assert!(find(&keys, &header("RS256", Some("new"))).is_none());
+        assert!(find(&keys, &header("EdDSA", Some("gone"))).is_none());
+    }
+
+    #[test]
+    fn test_shared_secrets_and_mismatched_keys_are_ignored() {
+        let set: JwkSet = serde_json::from_str(
+            r#"{"keys": [
+                {"kty": "oct", "kid": "hmac", "k": "c2VjcmV0"},
+                {"kty": "oct", "k": "c2VjcmV0", "alg": "HS256"},
+                {"kty": "OKP", "crv": "Ed25519", "kid": "enc", "x": "AAAA", "use": "enc"},
+                {"kty": "OKP", "crv": "Ed25519", "kid": "rsa", "x": "AAAA", "alg": "RS256"},
+                {"kty": "OKP", "crv": "Ed25519", "kid": "ok", "x": "AAAA", "alg": "EdDSA", "use": "sig"}
+            ]}"#,
+        )
+        .unwrap();
+        let keys = usable(&set);
+        assert_eq!(keys.as_ref().unwrap().len(), 1);
+        let hs256 = Header {
+            alg: "HS256".into(),
+            kid: None,
+        };
+        assert!(find(&keys, &hs256).is_none());
+        let eddsa = Header {
+            alg: "EdDSA".into(),
+            kid: Some("ok".into()),
+        };
+        assert!(find(&keys, &eddsa).is_some());
+    }
+}
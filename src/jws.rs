@@ -0,0 +1,161 @@
+use crate::jwks::JwksKeys;
+use crate::{base64, IntegrityError, SignatureKey};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Header {
+    pub(crate) alg: String,
+    pub(crate) kid: Option<String>,
+}
+
+// A compact JWS (`header.payload.signature`), as returned by endpoints that wrap their JSON in a
+// signed JWT. The body may also be the token as a JSON string.
+pub(crate) struct Jws<'a> {
+    pub(crate) header: Header,
+    signing_input: &'a [u8],
+    payload: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+// Where `jws_payload` gets its verification key from.
+pub(crate) enum JwsKey {
+    Static(SignatureKey),
+    Jwks(Arc<JwksKeys>),
+}
+
+impl JwsKey {
+    pub(crate) async fn unwrap(&self, body: &[u8]) -> Result<Vec<u8>, IntegrityError> {
+        let jws = Jws::parse(body)?;
+        match self {
+            Self::Static(key) => jws.verify(key)?,
+            Self::Jwks(keys) => jws.verify(&keys.key_for(&jws.header).await?)?,
+        }
+        jws.into_payload()
+    }
+}
+
+impl Clone for JwsKey {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Static(key) => Self::Static(key.clone()),
+            Self::Jwks(keys) => Self::Jwks(keys.clone()),
+        }
+    }
+}
+
+fn invalid(reason: &str) -> IntegrityError {
+    IntegrityError::InvalidToken(reason.to_owned())
+}
+
+impl<'a> Jws<'a> {
+    pub(crate) fn parse(body: &'a [u8]) -> Result<Self, IntegrityError> {
+        let token = body.trim_ascii();
+        let token = token
+            .strip_prefix(b"\"")
+            .and_then(|token| token.strip_suffix(b"\""))
+            .unwrap_or(token);
+        let token = std::str::from_utf8(token).map_err(|_| invalid("not a compact JWS"))?;
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("not a compact JWS"));
+        };
+        let decode = |part: &str, what: &str| {
+            base64::decode(part).ok_or_else(|| invalid(&format!("{what} is not base64url")))
+        };
+        let header = serde_json::from_slice(&decode(header, "header")?)
+            .map_err(|_| invalid("header is not a JOSE header"))?;
+        Ok(Self {
+            header,
+            signing_input: &token.as_bytes()[..token.len() - signature.len() - 1],
+            payload: decode(payload, "payload")?,
+            signature: decode(signature, "signature")?,
+        })
+    }
+
+    // The header's `alg` has to match the key type, so a token can't pick a weaker algorithm
+    // (or `none`) than the one configured.
+    pub(crate) fn verify(&self, key: &SignatureKey) -> Result<(), IntegrityError> {
+        let expected = key.jws_alg();
+        if self.header.alg != expected {
+            return Err(invalid(&format!(
+                "alg {} does not match the {expected} key",
+                self.header.alg
+            )));
+        }
+        match key.verify_jws(self.signing_input, &self.signature) {
+            true => Ok(()),
+            false => Err(invalid("signature does not match")),
+        }
+    }
+
+    // The payload, once its `exp` and `nbf` claims (when present) allow it to be used.
+    pub(crate) fn into_payload(self) -> Result<Vec<u8>, IntegrityError> {
+        #[derive(Deserialize)]
+        struct Times {
+            exp: Option<f64>,
+            nbf: Option<f64>,
+        }
+        if let Ok(times) = serde_json::from_slice::<Times>(&self.payload) {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+            if times.exp.is_some_and(|exp| now >= exp) {
+                return Err(invalid("token has expired"));
+            }
+            if times.nbf.is_some_and(|nbf| now < nbf) {
+                return Err(invalid("token is not valid yet"));
+            }
+        }
+        Ok(self.payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::hmac;
+
+    fn hs256(secret: &[u8], header: &str, claims: &str) -> String {
+        let input = format!(
+            "{}.{}",
+            base64::encode_url(header.as_bytes()),
+            base64::encode_url(claims.as_bytes())
+        );
+        let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, secret), input.as_bytes());
+        format!("{input}.{}", base64::encode_url(tag.as_ref()))
+    }
+
+    #[test]
+    fn test_signed_token_yields_its_claims() {
+        let key = SignatureKey::HmacSha256(b"secret".to_vec());
+        let token = hs256(b"secret", r#"{"alg":"HS256"}"#, r#"{"price":7}"#);
+        let body = format!("\"{token}\"\n");
+
+        let jws = Jws::parse(body.as_bytes()).unwrap();
+        jws.verify(&key).unwrap();
+        assert_eq!(jws.into_payload().unwrap(), br#"{"price":7}"#);
+
+        let forged = hs256(b"other", r#"{"alg":"HS256"}"#, r#"{"price":7}"#);
+        assert!(Jws::parse(forged.as_bytes()).unwrap().verify(&key).is_err());
+        let unsigned = hs256(b"secret", r#"{"alg":"none"}"#, r#"{"price":7}"#);
+        assert!(Jws::parse(unsigned.as_bytes())
+            .unwrap()
+            .verify(&key)
+            .is_err());
+        assert!(Jws::parse(b"{\"price\":7}").is_err());
+    }
+
+    #[test]
+    fn test_expired_token_is_rejected() {
+        let token = hs256(b"s", r#"{"alg":"HS256"}"#, r#"{"exp":1000,"price":7}"#);
+        assert_eq!(
+            Jws::parse(token.as_bytes()).unwrap().into_payload(),
+            Err(IntegrityError::InvalidToken("token has expired".into()))
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+// Read handle for `poll_latest`. Readers clone an `Arc` out of the cell, so a slow reader never
+// holds the lock while the poller stores the next value.
+#[derive(Debug)]
+pub struct Latest<T> {
+    cell: Arc<RwLock<Option<Stored<T>>>>,
+}
+
+#[derive(Debug)]
+struct Stored<T> {
+    value: Arc<T>,
+    received: Instant,
+    ttl: Option<Duration>,
+}
+
+impl<T> Latest<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            cell: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn get(&self) -> Option<Arc<T>> {
+        let stored = self.cell.read().unwrap();
+        stored.as_ref().map(|stored| stored.value.clone())
+    }
+
+    // Like `get`, but `None` once the value has outlived its TTL (see `PollMeta::ttl`). Values
+    // delivered without a TTL stay fresh.
+    pub fn latest_if_fresh(&self) -> Option<Arc<T>> {
+        let stored = self.cell.read().unwrap();
+        let stored = stored.as_ref()?;
+        match stored.ttl {
+            Some(ttl) if stored.received.elapsed() > ttl => None,
+            _ => Some(stored.value.clone()),
+        }
+    }
+
+    pub(crate) fn set(&self, value: T, ttl: Option<Duration>) {
+        *self.cell.write().unwrap() = Some(Stored {
+            value: Arc::new(value),
+            received: Instant::now(),
+            ttl,
+        });
+    }
+}
+
+impl<T> Clone for Latest<T> {
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_expires_after_ttl() {
+        let latest = Latest::new();
+        assert_eq!(latest.latest_if_fresh(), None);
+
+        latest.set(1, None);
+        assert_eq!(latest.latest_if_fresh().as_deref(), Some(&1));
+        latest.set(2, Some(Duration::ZERO));
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(latest.latest_if_fresh(), None);
+        assert_eq!(latest.get().as_deref(), Some(&2));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::seed::Decoder;
+use crate::stream::ArraySplitter;
+
+// The elements of an array payload that decoded, plus the ones that did not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partial<T> {
+    pub items: Vec<T>,
+    pub rejected: Vec<RejectedItem>,
+}
+
+// An array element that could not be deserialized into `T`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedItem {
+    pub index: usize,
+    pub error: String,
+    pub raw: String,
+}
+
+// Decodes each element of a top-level array on its own, so one malformed record doesn't fail
+// the others. A body that isn't an array at all still fails as a whole.
+pub(crate) fn decode_elements<T>(
+    decoder: &Decoder<T>,
+    body: &[u8],
+) -> Result<Partial<T>, serde_json::Error> {
+    let mut splitter = ArraySplitter::default();
+    let mut partial = Partial {
+        items: Vec::new(),
+        rejected: Vec::new(),
+    };
+    let mut index = 0;
+    splitter.feed(body, &mut |raw| {
+        match decoder.decode(raw) {
+            Ok(item) => partial.items.push(item),
+            Err(e) => partial.rejected.push(RejectedItem {
+                index,
+                error: e.to_string(),
+                raw: String::from_utf8_lossy(raw).into_owned(),
+            }),
+        }
+        index += 1;
+        Ok(())
+    })?;
+    splitter.finish()?;
+    Ok(partial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Trade {
+        id: u32,
+        price: f64,
+    }
+
+    #[test]
+    fn test_bad_elements_are_reported_with_their_index() {
+        let body = br#"[{"id": 1, "price": 2.5}, {"id": "x"}, {"id": 3, "price": 1}]"#;
+        let partial = decode_elements(&Decoder::<Trade>::owned(), body).unwrap();
+        assert_eq!(
+            partial.items,
+            [Trade { id: 1, price: 2.5 }, Trade { id: 3, price: 1.0 }]
+        );
+        assert_eq!(partial.rejected.len(), 1);
+        assert_eq!(partial.rejected[0].index, 1);
+        assert_eq!(partial.rejected[0].raw, r#"{"id": "x"}"#);
+        assert!(decode_elements(&Decoder::<Trade>::owned(), b"{}").is_err());
+    }
+}
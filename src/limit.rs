@@ -0,0 +1,392 @@
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, OwnedSemaphorePermit, Semaphore};
+
+// Scheduling class for shared budgets: while a higher tier is waiting for a concurrency slot or
+// a rate-limiter token, lower tiers keep waiting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl Priority {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Clone)]
+pub struct BandwidthBudget {
+    inner: Arc<Mutex<Bucket>>,
+}
+
+struct Bucket {
+    rate: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.rate).min(self.rate);
+        self.updated = now;
+    }
+
+    fn wait(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.available > 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.rate)
+        }
+    }
+}
+
+impl BandwidthBudget {
+    pub fn bytes_per_sec(rate: u64) -> Self {
+        let rate = rate.max(1) as f64;
+        Self {
+            inner: Arc::new(Mutex::new(Bucket {
+                rate,
+                available: rate,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    pub(crate) fn wait_time(&self) -> Duration {
+        self.inner.lock().unwrap().wait(Instant::now())
+    }
+
+    pub(crate) fn consume(&self, bytes: u64) {
+        let mut bucket = self.inner.lock().unwrap();
+        bucket.refill(Instant::now());
+        bucket.available -= bytes as f64;
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<Mutex<TokenBucket>>,
+}
+
+struct TokenBucket {
+    capacity: f64,
+    per_sec: f64,
+    tokens: f64,
+    updated: Instant,
+    waiting: [usize; 3],
+}
+
+impl TokenBucket {
+    fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.per_sec))
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        let capacity = requests.max(1) as f64;
+        Self {
+            inner: Arc::new(Mutex::new(TokenBucket {
+                capacity,
+                per_sec: capacity / per.as_secs_f64().max(f64::EPSILON),
+                tokens: capacity,
+                updated: Instant::now(),
+                waiting: [0; 3],
+            })),
+        }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    pub async fn acquire(&self) -> Duration {
+        self.acquire_as(Priority::Normal).await
+    }
+
+    // Tokens are only taken while no higher tier is waiting for one.
+    pub(crate) async fn acquire_as(&self, priority: Priority) -> Duration {
+        let mut waited = Duration::ZERO;
+        let mut queued = None;
+        loop {
+            let result = {
+                let mut bucket = self.inner.lock().unwrap();
+                if bucket.waiting[..priority.index()].iter().any(|&n| n > 0) {
+                    Err(Duration::from_secs_f64(1.0 / bucket.per_sec))
+                } else {
+                    bucket.take(Instant::now())
+                }
+            };
+            match result {
+                Ok(()) => return waited,
+                Err(wait) => {
+                    queued.get_or_insert_with(|| Queued::new(&self.inner, priority));
+                    tokio::time::sleep(wait).await;
+                    waited += wait;
+                }
+            }
+        }
+    }
+}
+
+// Counts a rate-limiter waiter for as long as it is waiting, including when it is cancelled.
+struct Queued {
+    bucket: Arc<Mutex<TokenBucket>>,
+    priority: usize,
+}
+
+impl Queued {
+    fn new(bucket: &Arc<Mutex<TokenBucket>>, priority: Priority) -> Self {
+        bucket.lock().unwrap().waiting[priority.index()] += 1;
+        Self {
+            bucket: bucket.clone(),
+            priority: priority.index(),
+        }
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        self.bucket.lock().unwrap().waiting[self.priority] -= 1;
+    }
+}
+
+// Caps how many pollers have a request in flight at once. Slots freed up go to the longest
+// waiting poller of the highest priority. A plain `Arc<Semaphore>` converts into a limit that
+// ignores priorities.
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    kind: LimitKind,
+    // Unknown for a semaphore, which may be given permits later.
+    max_in_flight: Option<usize>,
+}
+
+#[derive(Clone)]
+enum LimitKind {
+    Semaphore(Arc<Semaphore>),
+    Prioritized(Arc<Mutex<Slots>>),
+}
+
+pub(crate) struct Slots {
+    available: usize,
+    waiting: [VecDeque<oneshot::Sender<()>>; 3],
+}
+
+impl Slots {
+    // Hands a freed slot to the first waiter still listening, or returns it to the pool.
+    fn release(&mut self) {
+        for queue in &mut self.waiting {
+            while let Some(waiter) = queue.pop_front() {
+                if waiter.send(()).is_ok() {
+                    return;
+                }
+            }
+        }
+        self.available += 1;
+    }
+}
+
+pub(crate) enum ConcurrencyPermit {
+    // Held only to release the permit on drop.
+    Semaphore(#[allow(dead_code)] OwnedSemaphorePermit),
+    Prioritized(Arc<Mutex<Slots>>),
+}
+
+impl Drop for ConcurrencyPermit {
+    fn drop(&mut self) {
+        if let Self::Prioritized(slots) = self {
+            slots.lock().unwrap().release();
+        }
+    }
+}
+
+impl ConcurrencyLimit {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            kind: LimitKind::Prioritized(Arc::new(Mutex::new(Slots {
+                available: max_in_flight,
+                waiting: Default::default(),
+            }))),
+            max_in_flight: Some(max_in_flight),
+        }
+    }
+
+    pub(crate) fn max_in_flight(&self) -> Option<usize> {
+        self.max_in_flight
+    }
+
+    pub(crate) async fn acquire(&self, priority: Priority) -> Option<ConcurrencyPermit> {
+        let slots = match &self.kind {
+            LimitKind::Semaphore(semaphore) => {
+                let permit = semaphore.clone().acquire_owned().await.ok()?;
+                return Some(ConcurrencyPermit::Semaphore(permit));
+            }
+            LimitKind::Prioritized(slots) => slots,
+        };
+        let receiver = {
+            let mut state = slots.lock().unwrap();
+            if state.available > 0 && state.waiting.iter().all(VecDeque::is_empty) {
+                state.available -= 1;
+                return Some(ConcurrencyPermit::Prioritized(slots.clone()));
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiting[priority.index()].push_back(sender);
+            receiver
+        };
+        let mut waiter = Waiter {
+            receiver,
+            slots: slots.clone(),
+        };
+        (&mut waiter.receiver).await.ok()?;
+        Some(ConcurrencyPermit::Prioritized(waiter.slots.clone()))
+    }
+}
+
+impl From<Arc<Semaphore>> for ConcurrencyLimit {
+    fn from(semaphore: Arc<Semaphore>) -> Self {
+        Self {
+            kind: LimitKind::Semaphore(semaphore),
+            max_in_flight: None,
+        }
+    }
+}
+
+// A cancelled acquire may already have been handed a slot; it is passed on rather than lost.
+struct Waiter {
+    receiver: oneshot::Receiver<()>,
+    slots: Arc<Mutex<Slots>>,
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        self.receiver.close();
+        if self.receiver.try_recv().is_ok() {
+            self.slots.lock().unwrap().release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overdrawn_budget_waits_for_refill() {
+        let budget = BandwidthBudget::bytes_per_sec(1000);
+        assert_eq!(budget.wait_time(), Duration::ZERO);
+
+        budget.consume(1500);
+        let wait = budget.wait_time();
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_budget_is_shared_between_clones() {
+        let budget = BandwidthBudget::bytes_per_sec(100);
+        let other = budget.clone();
+
+        other.consume(200);
+        assert!(budget.wait_time() > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_refill_is_capped_at_one_second() {
+        let mut bucket = Bucket {
+            rate: 100.0,
+            available: 100.0,
+            updated: Instant::now(),
+        };
+        bucket.refill(bucket.updated + Duration::from_secs(60));
+        assert_eq!(bucket.available, 100.0);
+    }
+
+    #[test]
+    fn test_token_bucket_allows_burst_then_waits() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket {
+            capacity: 2.0,
+            per_sec: 2.0 / 60.0,
+            tokens: 2.0,
+            updated: start,
+            waiting: [0; 3],
+        };
+
+        assert_eq!(bucket.take(start), Ok(()));
+        assert_eq!(bucket.take(start), Ok(()));
+        let wait = bucket.take(start).unwrap_err();
+        assert_eq!(wait.as_secs(), 30);
+        assert_eq!(bucket.take(start + Duration::from_secs(30)), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_is_shared_between_clones() {
+        let limiter = RateLimiter::new(1, Duration::from_millis(50));
+        let other = limiter.clone();
+
+        assert_eq!(limiter.acquire().await, Duration::ZERO);
+        assert!(other.acquire().await > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_freed_slot_goes_to_highest_priority_waiter() {
+        let limit = ConcurrencyLimit::new(1);
+        let held = limit.acquire(Priority::Normal).await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut waiters = Vec::new();
+        for priority in [Priority::Low, Priority::Normal, Priority::High] {
+            let (limit, order) = (limit.clone(), order.clone());
+            waiters.push(tokio::spawn(async move {
+                let _permit = limit.acquire(priority).await;
+                order.lock().unwrap().push(priority);
+            }));
+            tokio::task::yield_now().await;
+        }
+        drop(held);
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            [Priority::High, Priority::Normal, Priority::Low]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_passes_its_slot_on() {
+        let limit = ConcurrencyLimit::new(1);
+        let held = limit.acquire(Priority::Normal).await.unwrap();
+        let cancelled = tokio::spawn({
+            let limit = limit.clone();
+            async move { limit.acquire(Priority::High).await.map(drop) }
+        });
+        tokio::task::yield_now().await;
+        cancelled.abort();
+        let _ = cancelled.await;
+        drop(held);
+
+        assert!(limit.acquire(Priority::Low).await.is_some());
+    }
+}
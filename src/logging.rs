@@ -0,0 +1,241 @@
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+use tracing::Level;
+
+macro_rules! log_at {
+    ($level:expr, $($arg:tt)+) => {
+        match $level {
+            ::tracing::Level::ERROR => ::tracing::error!($($arg)+),
+            ::tracing::Level::WARN => ::tracing::warn!($($arg)+),
+            ::tracing::Level::INFO => ::tracing::info!($($arg)+),
+            ::tracing::Level::DEBUG => ::tracing::debug!($($arg)+),
+            ::tracing::Level::TRACE => ::tracing::trace!($($arg)+),
+        }
+    };
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LogConfig {
+    pub(crate) enabled: bool,
+    pub(crate) transient_level: Level,
+    pub(crate) repeated_level: Level,
+    pub(crate) repeated_after: u32,
+    pub(crate) min_interval: Duration,
+    pub(crate) transitions_only: bool,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            transient_level: Level::ERROR,
+            repeated_level: Level::ERROR,
+            repeated_after: 3,
+            min_interval: Duration::ZERO,
+            transitions_only: false,
+        }
+    }
+}
+
+// Logged as fields next to the message, so failures can be indexed by attempt and status.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ErrorFields {
+    pub(crate) attempt: u32,
+    pub(crate) elapsed: Duration,
+    pub(crate) status: Option<u16>,
+}
+
+pub(crate) struct ErrorLogger {
+    config: LogConfig,
+    consecutive: u32,
+    suppressed: u64,
+    last_logged: Option<Instant>,
+    failing_since: Option<Instant>,
+}
+
+impl ErrorLogger {
+    pub(crate) fn new(config: LogConfig) -> Self {
+        Self {
+            config,
+            consecutive: 0,
+            suppressed: 0,
+            last_logged: None,
+            failing_since: None,
+        }
+    }
+
+    // Returns the number of failed polls and the outage duration when this ends a failure streak.
+    pub(crate) fn success(&mut self, now: Instant) -> Option<(u32, Duration)> {
+        let failures = std::mem::take(&mut self.consecutive);
+        let since = self.failing_since.take()?;
+        let outage = now.saturating_duration_since(since);
+        if self.config.enabled && self.config.transitions_only {
+            tracing::info!(
+                "Recovered after {} failed poll(s) over {:?}",
+                failures,
+                outage
+            );
+        }
+        Some((failures, outage))
+    }
+
+    pub(crate) fn error(&mut self, now: Instant, error: &dyn Debug, fields: ErrorFields) {
+        let attempt = fields.attempt;
+        let elapsed_ms = fields.elapsed.as_millis() as u64;
+        let status = fields.status;
+        self.consecutive = self.consecutive.saturating_add(1);
+        let started_failing = self.failing_since.is_none();
+        self.failing_since.get_or_insert(now);
+
+        if self.config.transitions_only {
+            if self.config.enabled && started_failing {
+                log_at!(
+                    self.config.transient_level,
+                    attempt,
+                    elapsed_ms,
+                    status,
+                    "Started failing: {:?}",
+                    error
+                );
+            }
+            return;
+        }
+
+        let Some(level) = self.admit(now) else {
+            return;
+        };
+
+        let suppressed = std::mem::take(&mut self.suppressed);
+        if suppressed > 0 {
+            log_at!(
+                level,
+                attempt,
+                elapsed_ms,
+                status,
+                "Failed to fetch data: {:?} (error repeated {}×, suppressing)",
+                error,
+                suppressed
+            );
+        } else {
+            log_at!(
+                level,
+                attempt,
+                elapsed_ms,
+                status,
+                "Failed to fetch data: {:?}",
+                error
+            );
+        }
+    }
+
+    fn admit(&mut self, now: Instant) -> Option<Level> {
+        if !self.config.enabled {
+            return None;
+        }
+        if let Some(last) = self.last_logged {
+            if now.saturating_duration_since(last) < self.config.min_interval {
+                self.suppressed += 1;
+                return None;
+            }
+        }
+        self.last_logged = Some(now);
+        Some(if self.consecutive > self.config.repeated_after {
+            self.config.repeated_level
+        } else {
+            self.config.transient_level
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_failures_switch_level() {
+        let mut logger = ErrorLogger::new(LogConfig {
+            repeated_level: Level::DEBUG,
+            repeated_after: 2,
+            ..LogConfig::default()
+        });
+        let now = Instant::now();
+
+        assert_eq!(logger.admit_after_error(now), Some(Level::ERROR));
+        assert_eq!(logger.admit_after_error(now), Some(Level::ERROR));
+        assert_eq!(logger.admit_after_error(now), Some(Level::DEBUG));
+
+        logger.success(now);
+        assert_eq!(logger.admit_after_error(now), Some(Level::ERROR));
+    }
+
+    #[test]
+    fn test_rate_limit_counts_suppressed_errors() {
+        let mut logger = ErrorLogger::new(LogConfig {
+            min_interval: Duration::from_secs(60),
+            ..LogConfig::default()
+        });
+        let start = Instant::now();
+
+        logger.error(start, &"down", ErrorFields::default());
+        for i in 1..=120 {
+            logger.error(
+                start + Duration::from_millis(i * 100),
+                &"down",
+                ErrorFields::default(),
+            );
+        }
+        assert_eq!(logger.suppressed, 120);
+
+        logger.error(
+            start + Duration::from_secs(61),
+            &"down",
+            ErrorFields::default(),
+        );
+        assert_eq!(logger.suppressed, 0);
+    }
+
+    #[test]
+    fn test_transitions_only_tracks_outage() {
+        let mut logger = ErrorLogger::new(LogConfig {
+            transitions_only: true,
+            ..LogConfig::default()
+        });
+        let start = Instant::now();
+
+        for i in 0..5 {
+            logger.error(
+                start + Duration::from_secs(i),
+                &"down",
+                ErrorFields::default(),
+            );
+        }
+        assert_eq!(logger.consecutive, 5);
+        assert_eq!(logger.failing_since, Some(start));
+        assert_eq!(logger.last_logged, None);
+
+        assert_eq!(
+            logger.success(start + Duration::from_secs(10)),
+            Some((5, Duration::from_secs(10)))
+        );
+        assert_eq!(logger.success(start + Duration::from_secs(11)), None);
+        assert_eq!(logger.consecutive, 0);
+        assert_eq!(logger.failing_since, None);
+    }
+
+    #[test]
+    fn test_disabled_logging_admits_nothing() {
+        let mut logger = ErrorLogger::new(LogConfig {
+            enabled: false,
+            ..LogConfig::default()
+        });
+
+        assert_eq!(logger.admit_after_error(Instant::now()), None);
+    }
+
+    impl ErrorLogger {
+        fn admit_after_error(&mut self, now: Instant) -> Option<Level> {
+            self.consecutive += 1;
+            self.admit(now)
+        }
+    }
+}
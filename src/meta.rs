@@ -0,0 +1,80 @@
+use crate::{CertificateInfo, RateLimitInfo, RedirectHop};
+use reqwest::header::{HeaderMap, CACHE_CONTROL};
+use reqwest::StatusCode;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct PollMeta {
+    pub tick: u64,
+    pub elapsed: Duration,
+    pub status: Option<StatusCode>,
+    pub attempts: u32,
+    pub rate_limit: Option<RateLimitInfo>,
+    pub request_id: Option<String>,
+    pub derived: BTreeMap<String, f64>,
+    // How long the delivered value stays valid: `value_ttl` if configured, otherwise the
+    // response's `Cache-Control: max-age`.
+    pub ttl: Option<Duration>,
+    // Receive time minus the payload's own timestamp; see `timestamp_field`.
+    pub data_age: Option<Duration>,
+    // Server clock minus local clock in ms, estimated from the `Date` header.
+    pub clock_skew_ms: Option<i64>,
+    // TLS peer certificate of the response; `None` unless `certificate_info` is enabled.
+    pub certificate: Option<CertificateInfo>,
+    // Redirects followed by the last request, in order; empty unless `record_redirects` is on.
+    pub redirects: Vec<RedirectHop>,
+    // URL of the poll's primary request as sent, query included; `None` if nothing was sent.
+    pub url: Option<String>,
+}
+
+impl PollMeta {
+    // A single successful attempt with nothing else known yet; callers fill in the rest.
+    pub(crate) fn new(tick: u64) -> Self {
+        Self {
+            tick,
+            elapsed: Duration::ZERO,
+            status: None,
+            attempts: 1,
+            rate_limit: None,
+            request_id: None,
+            derived: BTreeMap::new(),
+            ttl: None,
+            data_age: None,
+            clock_skew_ms: None,
+            certificate: None,
+            redirects: Vec::new(),
+            url: None,
+        }
+    }
+}
+
+pub(crate) fn max_age(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|directive| {
+            let (name, seconds) = directive.trim().split_once('=')?;
+            name.eq_ignore_ascii_case("max-age")
+                .then(|| seconds.trim_matches('"').parse().ok())?
+        })
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_age_is_read_from_cache_control() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(max_age(&headers), None);
+        headers.insert(CACHE_CONTROL, "public, max-age=30".parse().unwrap());
+        assert_eq!(max_age(&headers), Some(Duration::from_secs(30)));
+        headers.insert(CACHE_CONTROL, "no-store, Max-Age=x".parse().unwrap());
+        assert_eq!(max_age(&headers), None);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::CertificateInfo;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+#[cfg(any(feature = "prometheus", feature = "statsd"))]
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MetricsSnapshot {
+    pub successes: u64,
+    pub errors: u64,
+    // Failed polls since the last good one, and when that was. Polls answered with 304, or with
+    // a status treated as empty, count as good.
+    pub consecutive_errors: u64,
+    pub last_ok_at: Option<SystemTime>,
+    pub missed_ticks: u64,
+    // Decoded response bytes, and the same responses as received before decompression.
+    pub bytes_received: u64,
+    pub wire_bytes_received: u64,
+    pub body_high_water_bytes: u64,
+    // Server minus local clock from the last `Date` header; positive when the server is ahead.
+    pub clock_skew_ms: Option<i64>,
+    // `notAfter` of the last TLS peer certificate; see `certificate_info`.
+    pub certificate_expires_at: Option<SystemTime>,
+    // Deliveries waiting in the `delivery_queue`, and those its overflow policy discarded.
+    pub queue_depth: u64,
+    pub queue_dropped: u64,
+    // Decoded body sizes in bytes, and the time `T` took to deserialize in microseconds.
+    pub payload_bytes: Distribution,
+    pub parse_micros: Distribution,
+}
+
+const BUCKETS: usize = 32;
+
+// A power-of-two histogram: `buckets[i]` counts the values in `(2^(i-1), 2^i]`, the last bucket
+// also everything larger.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Distribution {
+    pub count: u64,
+    pub sum: u64,
+    pub max: u64,
+    pub buckets: [u64; BUCKETS],
+}
+
+impl Distribution {
+    pub fn bucket_bound(index: usize) -> u64 {
+        match index {
+            i if i >= BUCKETS - 1 => u64::MAX,
+            i => 1 << i,
+        }
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+    }
+
+    // Upper bound of the bucket holding the `q` quantile, capped at the largest value seen.
+    pub fn quantile(&self, q: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        let index = self.buckets.iter().position(|&count| {
+            seen += count;
+            seen >= rank
+        })?;
+        Some(Self::bucket_bound(index).min(self.max))
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    count: AtomicU64,
+    sum: AtomicU64,
+    max: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Histogram {
+    fn record(&self, value: u64) {
+        let index = match value {
+            0 | 1 => 0,
+            v => (u64::BITS - (v - 1).leading_zeros()) as usize,
+        };
+        self.buckets[index.min(BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Distribution {
+        Distribution {
+            count: self.count.load(Ordering::Relaxed),
+            sum: self.sum.load(Ordering::Relaxed),
+            max: self.max.load(Ordering::Relaxed),
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Metrics {
+    successes: AtomicU64,
+    errors: AtomicU64,
+    consecutive_errors: AtomicU64,
+    last_ok_at: Mutex<Option<SystemTime>>,
+    missed_ticks: AtomicU64,
+    bytes_received: AtomicU64,
+    wire_bytes_received: AtomicU64,
+    body_high_water: AtomicU64,
+    queue_depth: AtomicU64,
+    queue_dropped: AtomicU64,
+    payload_bytes: Histogram,
+    parse_micros: Histogram,
+    last_request_id: Mutex<Option<String>>,
+    clock_skew_ms: Mutex<Option<i64>>,
+    certificate: Mutex<Option<CertificateInfo>>,
+    #[cfg(feature = "prometheus")]
+    pub(crate) prometheus: OnceLock<crate::prometheus::PrometheusMetrics>,
+    #[cfg(feature = "statsd")]
+    pub(crate) statsd: OnceLock<crate::statsd::StatsdEmitter>,
+}
+
+impl Metrics {
+    #[cfg_attr(
+        not(any(feature = "prometheus", feature = "statsd")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn record_success(&self, latency: Duration, bytes: u64, wire_bytes: u64) {
+        self.successes.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(bytes, Ordering::Relaxed);
+        self.wire_bytes_received
+            .fetch_add(wire_bytes, Ordering::Relaxed);
+        #[cfg(feature = "prometheus")]
+        if let Some(prometheus) = self.prometheus.get() {
+            prometheus.record_success(latency, bytes, wire_bytes);
+        }
+        #[cfg(feature = "statsd")]
+        if let Some(statsd) = self.statsd.get() {
+            statsd.record_success(latency, bytes, wire_bytes);
+        }
+    }
+
+    pub(crate) fn record_ok(&self) {
+        self.consecutive_errors.store(0, Ordering::Relaxed);
+        *self.last_ok_at.lock().unwrap() = Some(SystemTime::now());
+    }
+
+    pub(crate) fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_errors.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "prometheus")]
+        if let Some(prometheus) = self.prometheus.get() {
+            prometheus.record_error();
+        }
+        #[cfg(feature = "statsd")]
+        if let Some(statsd) = self.statsd.get() {
+            statsd.record_error();
+        }
+    }
+
+    pub(crate) fn record_body_size(&self, bytes: u64) {
+        self.body_high_water.fetch_max(bytes, Ordering::Relaxed);
+        self.payload_bytes.record(bytes);
+        #[cfg(feature = "statsd")]
+        if let Some(statsd) = self.statsd.get() {
+            statsd.record_body_size(bytes);
+        }
+    }
+
+    pub(crate) fn record_parse_time(&self, elapsed: Duration) {
+        self.parse_micros.record(elapsed.as_micros() as u64);
+        #[cfg(feature = "statsd")]
+        if let Some(statsd) = self.statsd.get() {
+            statsd.record_parse_time(elapsed);
+        }
+    }
+
+    pub(crate) fn record_missed_ticks(&self, missed: u64) -> u64 {
+        self.missed_ticks.fetch_add(missed, Ordering::Relaxed) + missed
+    }
+
+    pub(crate) fn record_queue_depth(&self, depth: usize) {
+        self.queue_depth.store(depth as u64, Ordering::Relaxed);
+        #[cfg(feature = "prometheus")]
+        if let Some(prometheus) = self.prometheus.get() {
+            prometheus.record_queue_depth(depth);
+        }
+        #[cfg(feature = "statsd")]
+        if let Some(statsd) = self.statsd.get() {
+            statsd.record_queue_depth(depth);
+        }
+    }
+
+    pub(crate) fn record_queue_dropped(&self) {
+        self.queue_dropped.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "prometheus")]
+        if let Some(prometheus) = self.prometheus.get() {
+            prometheus.record_queue_dropped();
+        }
+    }
+
+    pub(crate) fn record_request_id(&self, id: String) {
+        *self.last_request_id.lock().unwrap() = Some(id);
+    }
+
+    pub(crate) fn last_request_id(&self) -> Option<String> {
+        self.last_request_id.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record_clock_skew(&self, skew_ms: i64) {
+        *self.clock_skew_ms.lock().unwrap() = Some(skew_ms);
+        #[cfg(feature = "prometheus")]
+        if let Some(prometheus) = self.prometheus.get() {
+            prometheus.record_clock_skew(skew_ms);
+        }
+    }
+
+    pub(crate) fn clock_skew_ms(&self) -> Option<i64> {
+        *self.clock_skew_ms.lock().unwrap()
+    }
+
+    pub(crate) fn record_certificate(&self, certificate: Option<CertificateInfo>) {
+        #[cfg(feature = "prometheus")]
+        if let (Some(prometheus), Some(certificate)) = (self.prometheus.get(), &certificate) {
+            prometheus.record_certificate_expiry(certificate.not_after);
+        }
+        *self.certificate.lock().unwrap() = certificate;
+    }
+
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            successes: self.successes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            consecutive_errors: self.consecutive_errors.load(Ordering::Relaxed),
+            last_ok_at: *self.last_ok_at.lock().unwrap(),
+            missed_ticks: self.missed_ticks.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            wire_bytes_received: self.wire_bytes_received.load(Ordering::Relaxed),
+            body_high_water_bytes: self.body_high_water.load(Ordering::Relaxed),
+            clock_skew_ms: self.clock_skew_ms(),
+            certificate_expires_at: self
+                .certificate
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|certificate| certificate.not_after),
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            queue_dropped: self.queue_dropped.load(Ordering::Relaxed),
+            payload_bytes: self.payload_bytes.snapshot(),
+            parse_micros: self.parse_micros.snapshot(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_buckets_and_quantiles() {
+        let histogram = Histogram::default();
+        for value in [0, 1, 2, 3, 4, 1000] {
+            histogram.record(value);
+        }
+        histogram.record(1 << 40);
+        let distribution = histogram.snapshot();
+        assert_eq!(distribution.count, 7);
+        assert_eq!(distribution.buckets[..4], [2, 1, 2, 0]);
+        assert_eq!(distribution.buckets[10], 1);
+        assert_eq!(distribution.buckets[BUCKETS - 1], 1);
+        assert_eq!(distribution.quantile(0.5), Some(4));
+        assert_eq!(distribution.quantile(0.8), Some(1024));
+        assert_eq!(Distribution::default().quantile(0.5), None);
+    }
+}
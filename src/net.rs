@@ -0,0 +1,83 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::SocketAddr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpFamily {
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    fn accepts(self, addr: &SocketAddr) -> bool {
+        match self {
+            Self::Any => true,
+            Self::V4 => addr.is_ipv4(),
+            Self::V6 => addr.is_ipv6(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    #[default]
+    Auto,
+    Http1Only,
+    Http2PriorKnowledge,
+}
+
+pub(crate) struct FamilyResolver {
+    family: IpFamily,
+}
+
+impl FamilyResolver {
+    pub(crate) fn new(family: IpFamily) -> Self {
+        Self { family }
+    }
+}
+
+impl Resolve for FamilyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let family = self.family;
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| family.accepts(addr))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("no {family:?} address found for {host}").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolver_filters_by_family() {
+        let resolver = FamilyResolver::new(IpFamily::V4);
+        let addrs: Vec<_> = resolver
+            .resolve("localhost".parse().unwrap())
+            .await
+            .unwrap()
+            .collect();
+
+        assert!(!addrs.is_empty());
+        assert!(addrs.iter().all(SocketAddr::is_ipv4));
+    }
+
+    #[test]
+    fn test_family_acceptance() {
+        let v4: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        let v6: SocketAddr = "[::1]:80".parse().unwrap();
+
+        assert!(IpFamily::Any.accepts(&v4) && IpFamily::Any.accepts(&v6));
+        assert!(IpFamily::V4.accepts(&v4) && !IpFamily::V4.accepts(&v6));
+        assert!(IpFamily::V6.accepts(&v6) && !IpFamily::V6.accepts(&v4));
+    }
+}
@@ -0,0 +1,238 @@
+use serde_json::{json, Map, Value};
+
+// RFC 7396: objects are merged recursively, `null` removes a member and anything else replaces
+// the target outright.
+pub(crate) fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("target was just made an object");
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+// RFC 6902. Operations are applied to a copy, so a failing patch leaves `target` untouched.
+pub(crate) fn json_patch(target: &mut Value, patch: &Value) -> Result<(), String> {
+    let ops = patch.as_array().ok_or("a JSON Patch must be an array")?;
+    let mut doc = target.clone();
+    for op in ops {
+        apply_op(&mut doc, op)?;
+    }
+    *target = doc;
+    Ok(())
+}
+
+// Produces an RFC 6902 patch that turns `from` into `to`. Objects are compared member by member
+// and equal-length arrays element by element; anything else is replaced whole.
+pub(crate) fn diff(from: &Value, to: &Value) -> Vec<Value> {
+    let mut ops = Vec::new();
+    diff_at(from, to, &mut String::new(), &mut ops);
+    ops
+}
+
+fn diff_at(from: &Value, to: &Value, path: &mut String, ops: &mut Vec<Value>) {
+    match (from, to) {
+        _ if from == to => {}
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, value) in old {
+                let len = push(path, key);
+                match new.get(key) {
+                    Some(next) => diff_at(value, next, path, ops),
+                    None => ops.push(json!({"op": "remove", "path": path})),
+                }
+                path.truncate(len);
+            }
+            for (key, value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                let len = push(path, key);
+                ops.push(json!({"op": "add", "path": path, "value": value}));
+                path.truncate(len);
+            }
+        }
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (index, (value, next)) in old.iter().zip(new).enumerate() {
+                let len = push(path, &index.to_string());
+                diff_at(value, next, path, ops);
+                path.truncate(len);
+            }
+        }
+        _ if path.is_empty() => ops.push(json!({"op": "add", "path": "", "value": to})),
+        _ => ops.push(json!({"op": "replace", "path": path, "value": to})),
+    }
+}
+
+// Appends an escaped reference token and returns the length to truncate back to.
+fn push(path: &mut String, token: &str) -> usize {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    len
+}
+
+fn apply_op(doc: &mut Value, op: &Value) -> Result<(), String> {
+    let field = |name: &str| {
+        op.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("operation is missing `{name}`"))
+    };
+    let value = || {
+        op.get("value")
+            .cloned()
+            .ok_or("operation is missing `value`")
+    };
+    let path = field("path")?;
+    match field("op")? {
+        "add" => add(doc, path, value()?),
+        "remove" => remove(doc, path).map(drop),
+        "replace" => {
+            *doc.pointer_mut(path).ok_or_else(|| missing(path))? = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = field("from")?;
+            if path.starts_with(&format!("{from}/")) {
+                return Err(format!("cannot move {from} into itself"));
+            }
+            let moved = remove(doc, from)?;
+            add(doc, path, moved)
+        }
+        "copy" => {
+            let from = field("from")?;
+            let copied = doc.pointer(from).ok_or_else(|| missing(from))?.clone();
+            add(doc, path, copied)
+        }
+        "test" => match doc.pointer(path) {
+            Some(actual) if *actual == value()? => Ok(()),
+            _ => Err(format!("test failed at {path}")),
+        },
+        other => Err(format!("unknown operation `{other}`")),
+    }
+}
+
+fn missing(path: &str) -> String {
+    format!("no value at {path}")
+}
+
+fn split(path: &str) -> Result<(&str, String), String> {
+    let (parent, last) = path
+        .rsplit_once('/')
+        .ok_or_else(|| format!("`{path}` is not a JSON pointer"))?;
+    Ok((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, key) = split(path)?;
+    match doc.pointer_mut(parent).ok_or_else(|| missing(parent))? {
+        Value::Object(map) => {
+            map.insert(key, value);
+        }
+        Value::Array(items) => {
+            let index = match key.as_str() {
+                "-" => items.len(),
+                index => index
+                    .parse()
+                    .ok()
+                    .filter(|&i| i <= items.len())
+                    .ok_or_else(|| format!("invalid array index at {path}"))?,
+            };
+            items.insert(index, value);
+        }
+        _ => return Err(format!("cannot add below a scalar at {path}")),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value, String> {
+    let (parent, key) = split(path)?;
+    let removed = match doc.pointer_mut(parent).ok_or_else(|| missing(parent))? {
+        Value::Object(map) => map.remove(&key),
+        Value::Array(items) => key
+            .parse()
+            .ok()
+            .filter(|&i| i < items.len())
+            .map(|i| items.remove(i)),
+        _ => None,
+    };
+    removed.ok_or_else(|| missing(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_patch() {
+        let mut doc = json!({"a": 1, "b": {"c": 2, "d": 3}, "e": [1]});
+        merge_patch(
+            &mut doc,
+            &json!({"a": null, "b": {"c": 4}, "e": [2], "f": true}),
+        );
+        assert_eq!(doc, json!({"b": {"c": 4, "d": 3}, "e": [2], "f": true}));
+    }
+
+    #[test]
+    fn test_json_patch_operations() {
+        let mut doc = json!({"items": [1, 3], "meta": {"a~b": 1}});
+        let patch = json!([
+            {"op": "add", "path": "/items/1", "value": 2},
+            {"op": "add", "path": "/items/-", "value": 4},
+            {"op": "replace", "path": "/meta/a~0b", "value": 2},
+            {"op": "copy", "from": "/items/0", "path": "/first"},
+            {"op": "move", "from": "/first", "path": "/meta/first"},
+            {"op": "remove", "path": "/items/0"},
+            {"op": "test", "path": "/items", "value": [2, 3, 4]}
+        ]);
+        json_patch(&mut doc, &patch).unwrap();
+        assert_eq!(
+            doc,
+            json!({"items": [2, 3, 4], "meta": {"a~b": 2, "first": 1}})
+        );
+    }
+
+    #[test]
+    fn test_failed_json_patch_leaves_document_untouched() {
+        let mut doc = json!({"a": 1});
+        let patch = json!([
+            {"op": "replace", "path": "/a", "value": 2},
+            {"op": "remove", "path": "/missing"}
+        ]);
+        assert!(json_patch(&mut doc, &patch).is_err());
+        assert_eq!(doc, json!({"a": 1}));
+    }
+
+    #[test]
+    fn test_diff_round_trips_through_json_patch() {
+        let from = json!({"a": 1, "b": {"c": [1, 2], "d": "x"}, "e/f": true, "g": [1]});
+        let to = json!({"b": {"c": [1, 3], "d": "x"}, "e/f": false, "g": [1, 2], "h": null});
+        let ops = diff(&from, &to);
+        assert_eq!(
+            ops,
+            [
+                json!({"op": "remove", "path": "/a"}),
+                json!({"op": "replace", "path": "/b/c/1", "value": 3}),
+                json!({"op": "replace", "path": "/e~1f", "value": false}),
+                json!({"op": "replace", "path": "/g", "value": [1, 2]}),
+                json!({"op": "add", "path": "/h", "value": null}),
+            ]
+        );
+
+        let mut doc = from;
+        json_patch(&mut doc, &Value::Array(ops)).unwrap();
+        assert_eq!(doc, to);
+        assert!(diff(&doc, &to).is_empty());
+    }
+}
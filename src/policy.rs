@@ -0,0 +1,163 @@
+use crate::{ConnectFailure, JsonPollerError};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusAction {
+    Retry,
+    Backoff,
+    Empty,
+    Error,
+    Stop,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StatusPolicy {
+    exact: HashMap<u16, StatusAction>,
+    ranges: Vec<(RangeInclusive<u16>, StatusAction)>,
+    connect: HashMap<ConnectFailure, StatusAction>,
+}
+
+impl StatusPolicy {
+    pub(crate) fn set(&mut self, status: u16, action: StatusAction) {
+        self.exact.insert(status, action);
+    }
+
+    pub(crate) fn set_range(&mut self, range: RangeInclusive<u16>, action: StatusAction) {
+        self.ranges.push((range, action));
+    }
+
+    pub(crate) fn rule(&self, status: StatusCode) -> Option<StatusAction> {
+        let code = status.as_u16();
+        self.exact.get(&code).copied().or_else(|| {
+            self.ranges
+                .iter()
+                .rev()
+                .find(|(range, _)| range.contains(&code))
+                .map(|(_, action)| *action)
+        })
+    }
+
+    pub(crate) fn set_connect(&mut self, failure: ConnectFailure, action: StatusAction) {
+        self.connect.insert(failure, action);
+    }
+
+    pub(crate) fn connect_rule(&self, error: &JsonPollerError) -> Option<StatusAction> {
+        self.connect.get(&error.connect_failure()?).copied()
+    }
+
+    pub(crate) fn action(&self, status: StatusCode) -> StatusAction {
+        self.rule(status).unwrap_or(StatusAction::Error)
+    }
+}
+
+// Attempts and backoff for `fetch_with_retries`. The delay doubles after every failure, starting
+// at `base_delay` and capped at `max_delay`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) base_delay: Duration,
+    pub(crate) max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    pub(crate) fn backoff(&self) -> Backoff {
+        Backoff::new(self.base_delay / 2, self.max_delay)
+    }
+}
+
+pub(crate) struct Backoff {
+    base: Duration,
+    max: Duration,
+    step: u32,
+}
+
+impl Backoff {
+    pub(crate) fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max, step: 0 }
+    }
+
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        self.step = self.step.saturating_add(1);
+        let factor = 2u32.saturating_pow(self.step.min(31));
+        self.base.saturating_mul(factor).min(self.max)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.step = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_rule_wins_over_range() {
+        let mut policy = StatusPolicy::default();
+        policy.set_range(400..=499, StatusAction::Stop);
+        policy.set(404, StatusAction::Empty);
+
+        assert_eq!(policy.action(StatusCode::NOT_FOUND), StatusAction::Empty);
+        assert_eq!(policy.action(StatusCode::UNAUTHORIZED), StatusAction::Stop);
+        assert_eq!(policy.action(StatusCode::BAD_GATEWAY), StatusAction::Error);
+        assert_eq!(policy.rule(StatusCode::OK), None);
+    }
+
+    #[test]
+    fn test_later_ranges_override_earlier_ones() {
+        let mut policy = StatusPolicy::default();
+        policy.set_range(500..=599, StatusAction::Backoff);
+        policy.set_range(502..=504, StatusAction::Retry);
+
+        assert_eq!(policy.action(StatusCode::BAD_GATEWAY), StatusAction::Retry);
+        assert_eq!(
+            policy.action(StatusCode::INTERNAL_SERVER_ERROR),
+            StatusAction::Backoff
+        );
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_millis(500), Duration::from_secs(3));
+
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(3));
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+}
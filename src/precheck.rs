@@ -0,0 +1,71 @@
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_LENGTH, ETAG, LAST_MODIFIED};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Fingerprint {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    content_length: Option<HeaderValue>,
+}
+
+impl Fingerprint {
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            etag: headers.get(ETAG).cloned(),
+            last_modified: headers.get(LAST_MODIFIED).cloned(),
+            content_length: headers.get(CONTENT_LENGTH).cloned(),
+        }
+    }
+
+    // Only validators present on both sides are compared; with none in common there is nothing
+    // to go on, so the payload counts as changed.
+    pub(crate) fn unchanged(&self, current: &Self) -> bool {
+        let pairs = [
+            (&self.etag, &current.etag),
+            (&self.last_modified, &current.last_modified),
+            (&self.content_length, &current.content_length),
+        ];
+        let mut compared = 0;
+        for (previous, current) in pairs {
+            if let (Some(previous), Some(current)) = (previous, current) {
+                if previous != current {
+                    return false;
+                }
+                compared += 1;
+            }
+        }
+        compared > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(reqwest::header::HeaderName, &'static str)]) -> Fingerprint {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(name.clone(), HeaderValue::from_static(value));
+        }
+        Fingerprint::from_headers(&map)
+    }
+
+    #[test]
+    fn test_matching_validators_are_unchanged() {
+        let previous = headers(&[(ETAG, "\"v1\""), (CONTENT_LENGTH, "100")]);
+
+        assert!(previous.unchanged(&headers(&[(ETAG, "\"v1\"")])));
+        assert!(!previous.unchanged(&headers(&[(ETAG, "\"v2\"")])));
+        assert!(!previous.unchanged(&headers(&[(ETAG, "\"v1\""), (CONTENT_LENGTH, "101")])));
+    }
+
+    #[test]
+    fn test_missing_validators_count_as_changed() {
+        let previous = headers(&[(ETAG, "\"v1\"")]);
+
+        assert!(!previous.unchanged(&headers(&[(
+            LAST_MODIFIED,
+            "Tue, 13 Oct 2026 10:00:00 GMT"
+        )])));
+        assert!(!Fingerprint::default().unchanged(&Fingerprint::default()));
+    }
+}
@@ -0,0 +1,70 @@
+use futures_util::future::BoxFuture;
+use std::sync::RwLock;
+use tokio::sync::Mutex as AsyncMutex;
+
+pub(crate) type RefreshFn =
+    Box<dyn Fn() -> BoxFuture<'static, Result<String, String>> + Send + Sync>;
+
+// Holds the current pre-signed URL. Refreshes are serialized, so clones that hit the same
+// expired link at once share a single call to the hook.
+pub(crate) struct UrlRefresher {
+    refresh: RefreshFn,
+    current: RwLock<Option<String>>,
+    refreshing: AsyncMutex<()>,
+}
+
+impl UrlRefresher {
+    pub(crate) fn new(refresh: RefreshFn) -> Self {
+        Self {
+            refresh,
+            current: RwLock::new(None),
+            refreshing: AsyncMutex::new(()),
+        }
+    }
+
+    pub(crate) fn current(&self) -> Option<String> {
+        self.current.read().unwrap().clone()
+    }
+
+    // `failed` is the URL that was rejected; if another caller already replaced it, that URL is
+    // used without calling the hook again.
+    pub(crate) async fn refresh(&self, failed: &str) -> Result<String, String> {
+        let _guard = self.refreshing.lock().await;
+        if let Some(current) = self.current().filter(|current| current != failed) {
+            return Ok(current);
+        }
+        let url = (self.refresh)().await?;
+        *self.current.write().unwrap() = Some(url.clone());
+        Ok(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_concurrent_refreshes_share_one_call() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let refresher = UrlRefresher::new(Box::new(move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move { Ok(format!("http://signed/{n}")) })
+        }));
+
+        let (a, b) = tokio::join!(
+            refresher.refresh("http://old"),
+            refresher.refresh("http://old")
+        );
+        assert_eq!(a.unwrap(), "http://signed/0");
+        assert_eq!(b.unwrap(), "http://signed/0");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert_eq!(
+            refresher.refresh("http://signed/0").await.unwrap(),
+            "http://signed/1"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use prometheus::core::Collector;
+use prometheus::{Gauge, Histogram, HistogramOpts, IntCounter, IntGauge, Opts, Registry};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(crate) struct PrometheusMetrics {
+    successes: IntCounter,
+    errors: IntCounter,
+    bytes: IntCounter,
+    wire_bytes: IntCounter,
+    latency: Histogram,
+    last_success: Gauge,
+    clock_skew: Gauge,
+    certificate_expiry: Gauge,
+    queue_depth: IntGauge,
+    queue_dropped: IntCounter,
+}
+
+impl PrometheusMetrics {
+    pub(crate) fn register(registry: &Registry, name: &str, url: &str) -> prometheus::Result<Self> {
+        let opts = |metric: &str, help: &str| {
+            Opts::new(metric, help)
+                .const_label("poller", name)
+                .const_label("url", url)
+        };
+        let metrics = Self {
+            successes: IntCounter::with_opts(opts(
+                "json_poller_fetch_success_total",
+                "Successful polls",
+            ))?,
+            errors: IntCounter::with_opts(opts("json_poller_fetch_errors_total", "Failed polls"))?,
+            bytes: IntCounter::with_opts(opts(
+                "json_poller_received_bytes_total",
+                "Decoded response bytes received",
+            ))?,
+            wire_bytes: IntCounter::with_opts(opts(
+                "json_poller_received_wire_bytes_total",
+                "Response bytes received before decompression",
+            ))?,
+            latency: Histogram::with_opts(HistogramOpts::from(opts(
+                "json_poller_fetch_duration_seconds",
+                "Duration of successful polls",
+            )))?,
+            last_success: Gauge::with_opts(opts(
+                "json_poller_last_success_timestamp_seconds",
+                "Unix time of the last successful poll",
+            ))?,
+            clock_skew: Gauge::with_opts(opts(
+                "json_poller_clock_skew_seconds",
+                "Server clock minus local clock, from the Date header",
+            ))?,
+            certificate_expiry: Gauge::with_opts(opts(
+                "json_poller_certificate_expiry_timestamp_seconds",
+                "Unix time at which the server's TLS certificate expires",
+            ))?,
+            queue_depth: IntGauge::with_opts(opts(
+                "json_poller_queue_depth",
+                "Deliveries waiting in the delivery queue",
+            ))?,
+            queue_dropped: IntCounter::with_opts(opts(
+                "json_poller_queue_dropped_total",
+                "Deliveries discarded by the delivery queue's overflow policy",
+            ))?,
+        };
+
+        for (index, collector) in metrics.collectors().into_iter().enumerate() {
+            if let Err(e) = registry.register(collector) {
+                for registered in metrics.collectors().into_iter().take(index) {
+                    let _ = registry.unregister(registered);
+                }
+                return Err(e);
+            }
+        }
+        Ok(metrics)
+    }
+
+    pub(crate) fn collectors(&self) -> [Box<dyn Collector>; 10] {
+        [
+            Box::new(self.successes.clone()),
+            Box::new(self.errors.clone()),
+            Box::new(self.bytes.clone()),
+            Box::new(self.wire_bytes.clone()),
+            Box::new(self.latency.clone()),
+            Box::new(self.last_success.clone()),
+            Box::new(self.clock_skew.clone()),
+            Box::new(self.certificate_expiry.clone()),
+            Box::new(self.queue_depth.clone()),
+            Box::new(self.queue_dropped.clone()),
+        ]
+    }
+
+    pub(crate) fn record_success(&self, latency: Duration, bytes: u64, wire_bytes: u64) {
+        self.successes.inc();
+        self.bytes.inc_by(bytes);
+        self.wire_bytes.inc_by(wire_bytes);
+        self.latency.observe(latency.as_secs_f64());
+        if let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) {
+            self.last_success.set(now.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn record_error(&self) {
+        self.errors.inc();
+    }
+
+    pub(crate) fn record_clock_skew(&self, skew_ms: i64) {
+        self.clock_skew.set(skew_ms as f64 / 1000.0);
+    }
+
+    pub(crate) fn record_certificate_expiry(&self, not_after: SystemTime) {
+        if let Ok(at) = not_after.duration_since(UNIX_EPOCH) {
+            self.certificate_expiry.set(at.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn record_queue_depth(&self, depth: usize) {
+        self.queue_depth.set(depth as i64);
+    }
+
+    pub(crate) fn record_queue_dropped(&self) {
+        self.queue_dropped.inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collectors_are_labeled_per_poller() {
+        let registry = Registry::new();
+        let first = PrometheusMetrics::register(&registry, "a", "http://a").unwrap();
+        let _second = PrometheusMetrics::register(&registry, "b", "http://b").unwrap();
+
+        first.record_success(Duration::from_millis(20), 128, 32);
+        first.record_error();
+
+        let families = registry.gather();
+        let successes = families
+            .iter()
+            .find(|f| f.name() == "json_poller_fetch_success_total")
+            .unwrap();
+        assert_eq!(successes.get_metric().len(), 2);
+        let labeled_a = successes
+            .get_metric()
+            .iter()
+            .find(|m| m.get_label().iter().any(|l| l.value() == "a"))
+            .unwrap();
+        assert_eq!(labeled_a.get_counter().get_value(), 1.0);
+    }
+
+    #[test]
+    fn test_duplicate_registration_is_rolled_back() {
+        let registry = Registry::new();
+        let _first = PrometheusMetrics::register(&registry, "a", "http://a").unwrap();
+
+        assert!(PrometheusMetrics::register(&registry, "a", "http://a").is_err());
+        assert_eq!(registry.gather().len(), 10);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+// What `delivery_queue` does with a new delivery when the callback has fallen `capacity` behind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    // Discard the oldest queued delivery, so the callback catches up on the latest data.
+    #[default]
+    DropOldest,
+    // Discard the new delivery.
+    DropNewest,
+    // Hold the polling loop until there is room, like an unqueued poller but with slack.
+    Block,
+}
+
+// Buffers deliveries between the polling loop (the only producer) and the callback (the only
+// consumer), so a slow callback no longer delays the next poll.
+pub(crate) struct DeliveryQueue<I> {
+    capacity: usize,
+    overflow: Overflow,
+    state: Mutex<State<I>>,
+    readable: Notify,
+    writable: Notify,
+}
+
+struct State<I> {
+    items: VecDeque<I>,
+    closed: bool,
+}
+
+// The outcome of a `push`: the queue depth afterwards and the delivery that made room, if any.
+pub(crate) struct Pushed<I> {
+    pub(crate) depth: usize,
+    pub(crate) dropped: Option<I>,
+}
+
+impl<I> DeliveryQueue<I> {
+    pub(crate) fn new(capacity: usize, overflow: Overflow) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            overflow,
+            state: Mutex::new(State {
+                items: VecDeque::new(),
+                closed: false,
+            }),
+            readable: Notify::new(),
+            writable: Notify::new(),
+        }
+    }
+
+    pub(crate) async fn push(&self, item: I) -> Pushed<I> {
+        let mut item = Some(item);
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                let full = state.items.len() >= self.capacity;
+                if !full || self.overflow != Overflow::Block {
+                    let dropped = match self.overflow {
+                        _ if !full => None,
+                        Overflow::DropNewest => item.take(),
+                        _ => state.items.pop_front(),
+                    };
+                    state.items.extend(item);
+                    let depth = state.items.len();
+                    drop(state);
+                    self.readable.notify_one();
+                    return Pushed { depth, dropped };
+                }
+            }
+            self.writable.notified().await;
+        }
+    }
+
+    // `None` once the queue is closed and drained.
+    pub(crate) async fn pop(&self) -> Option<(I, usize)> {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(item) = state.items.pop_front() {
+                    let depth = state.items.len();
+                    drop(state);
+                    self.writable.notify_one();
+                    return Some((item, depth));
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.readable.notified().await;
+        }
+    }
+
+    pub(crate) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_overflow_policies() {
+        let oldest = DeliveryQueue::new(2, Overflow::DropOldest);
+        for i in 0..3 {
+            oldest.push(i).await;
+        }
+        let newest = DeliveryQueue::new(2, Overflow::DropNewest);
+        for i in 0..2 {
+            assert!(newest.push(i).await.dropped.is_none());
+        }
+        assert_eq!(newest.push(2).await.dropped, Some(2));
+        oldest.close();
+        newest.close();
+
+        assert_eq!(oldest.pop().await, Some((1, 1)));
+        assert_eq!(oldest.pop().await, Some((2, 0)));
+        assert_eq!(oldest.pop().await, None);
+        assert_eq!(newest.pop().await, Some((0, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_room() {
+        let queue = DeliveryQueue::new(1, Overflow::Block);
+        queue.push(0).await;
+        let push = queue.push(1);
+        tokio::pin!(push);
+        let waiting = tokio::time::timeout(std::time::Duration::from_millis(10), &mut push);
+        assert!(waiting.await.is_err());
+        assert_eq!(queue.pop().await, Some((0, 0)));
+        assert_eq!(push.await.depth, 1);
+    }
+}
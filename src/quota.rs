@@ -0,0 +1,110 @@
+use reqwest::header::HeaderMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Values below this are read as seconds until the reset, larger ones as a Unix timestamp.
+const EPOCH_CUTOFF: f64 = 1_000_000_000.0;
+
+// Rate-limit state reported by the server, from the `X-RateLimit-*` family (GitHub, Discord, and
+// most others) or the IETF `RateLimit-*` headers.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub struct RateLimitInfo {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    pub reset_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    pub(crate) fn from_headers(headers: &HeaderMap, now: SystemTime) -> Option<Self> {
+        let info = Self {
+            limit: number(headers, &["x-ratelimit-limit", "ratelimit-limit"]),
+            remaining: number(headers, &["x-ratelimit-remaining", "ratelimit-remaining"]),
+            reset_after: reset_after(headers, now),
+        };
+        (info != Self::default()).then_some(info)
+    }
+}
+
+fn value<'a>(headers: &'a HeaderMap, names: &[&str]) -> Option<&'a str> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+        .map(str::trim)
+}
+
+fn number(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    // The IETF draft allows a quota policy after the value, e.g. `100, 100;w=60`.
+    value(headers, names)?
+        .split([',', ';'])
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn reset_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    if let Some(after) = value(headers, &["x-ratelimit-reset-after"]) {
+        return Duration::try_from_secs_f64(after.parse().ok()?).ok();
+    }
+    let reset: f64 = value(headers, &["x-ratelimit-reset", "ratelimit-reset"])?
+        .parse()
+        .ok()?;
+    if reset < EPOCH_CUTOFF {
+        return Duration::try_from_secs_f64(reset).ok();
+    }
+    let at = UNIX_EPOCH + Duration::try_from_secs_f64(reset).ok()?;
+    Some(at.duration_since(now).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_github_style_headers() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let info = RateLimitInfo::from_headers(
+            &headers(&[
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "12"),
+                ("x-ratelimit-reset", "1700000090"),
+            ]),
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(info.limit, Some(5000));
+        assert_eq!(info.remaining, Some(12));
+        assert_eq!(info.reset_after, Some(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn test_ietf_and_delta_variants() {
+        let now = SystemTime::now();
+        let info = RateLimitInfo::from_headers(
+            &headers(&[
+                ("ratelimit-limit", "100, 100;w=60"),
+                ("ratelimit-remaining", "0"),
+                ("ratelimit-reset", "30"),
+            ]),
+            now,
+        )
+        .unwrap();
+        assert_eq!(info.limit, Some(100));
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.reset_after, Some(Duration::from_secs(30)));
+
+        let discord = headers(&[("x-ratelimit-reset-after", "1.5")]);
+        let info = RateLimitInfo::from_headers(&discord, now).unwrap();
+        assert_eq!(info.reset_after, Some(Duration::from_millis(1500)));
+
+        assert_eq!(RateLimitInfo::from_headers(&HeaderMap::new(), now), None);
+    }
+}
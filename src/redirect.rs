@@ -0,0 +1,84 @@
+use reqwest::redirect::{Attempt, Policy};
+use reqwest::StatusCode;
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::Mutex;
+
+// Same limit as reqwest's default policy.
+const MAX_REDIRECTS: usize = 10;
+
+// One followed redirect: the status it was answered with and where it pointed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectHop {
+    pub status: StatusCode,
+    pub location: String,
+}
+
+tokio::task_local! {
+    static HOPS: RefCell<Vec<RedirectHop>>;
+}
+
+// Follows redirects like the default policy and records each hop for the request being sent
+// inside `tracked`. reqwest runs the policy while the response future is polled, so the
+// task-local is in scope.
+pub(crate) fn policy() -> Policy {
+    Policy::custom(|attempt: Attempt<'_>| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        let hop = RedirectHop {
+            status: attempt.status(),
+            location: attempt.url().to_string(),
+        };
+        let _ = HOPS.try_with(|hops| hops.borrow_mut().push(hop));
+        attempt.follow()
+    })
+}
+
+pub(crate) async fn tracked<F: Future>(send: F) -> (F::Output, Vec<RedirectHop>) {
+    HOPS.scope(RefCell::new(Vec::new()), async {
+        let output = send.await;
+        (output, HOPS.with(|hops| hops.take()))
+    })
+    .await
+}
+
+// Reports the host a redirect chain ended on once, until requests land on the configured host
+// again.
+#[derive(Default)]
+pub(crate) struct HostWatch {
+    reported: Mutex<Option<String>>,
+}
+
+impl HostWatch {
+    pub(crate) fn check(&self, requested: &str, landed: &str) -> Option<String> {
+        let mut reported = self.reported.lock().unwrap();
+        if requested.eq_ignore_ascii_case(landed) {
+            *reported = None;
+            return None;
+        }
+        if reported.as_deref() == Some(landed) {
+            return None;
+        }
+        *reported = Some(landed.to_owned());
+        reported.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_change_is_reported_once() {
+        let watch = HostWatch::default();
+        assert_eq!(watch.check("api.example.com", "API.example.com"), None);
+        assert_eq!(
+            watch.check("api.example.com", "cdn.example.net"),
+            Some("cdn.example.net".into())
+        );
+        assert_eq!(watch.check("api.example.com", "cdn.example.net"), None);
+        assert_eq!(watch.check("api.example.com", "api.example.com"), None);
+        assert!(watch.check("api.example.com", "cdn.example.net").is_some());
+    }
+}
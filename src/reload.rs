@@ -0,0 +1,290 @@
+use crate::config::{GroupConfig, PollerConfig};
+use crate::group::stagger_offsets;
+use crate::{ConcurrencyLimit, ConfigError, JsonPoller, PollerTask};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+
+type Spawner<T, E> = Box<dyn Fn(&str, &JsonPoller<T>) -> PollerTask<E> + Send + Sync>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReloadSummary {
+    pub started: Vec<String>,
+    pub stopped: Vec<String>,
+    pub reconfigured: Vec<String>,
+}
+
+impl ReloadSummary {
+    pub fn is_empty(&self) -> bool {
+        self.started.is_empty() && self.stopped.is_empty() && self.reconfigured.is_empty()
+    }
+}
+
+// Keeps the running pollers in line with a `GroupConfig`. Each reload diffs the desired set
+// against what is running: new entries are started, removed ones stopped and changed ones
+// restarted with their new settings. Unchanged pollers keep running untouched.
+pub struct GroupReloader<T, E> {
+    spawner: Spawner<T, E>,
+    running: BTreeMap<String, (PollerConfig, PollerTask<E>)>,
+    max_in_flight: Option<usize>,
+    limit: Option<ConcurrencyLimit>,
+    drain: Duration,
+    log: bool,
+}
+
+impl<T, E> GroupReloader<T, E>
+where
+    T: DeserializeOwned + Send + 'static,
+    E: std::fmt::Debug + Send + 'static,
+{
+    pub fn new<F, Fut>(on_data: F) -> Self
+    where
+        F: Fn(&str, T) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send,
+    {
+        let spawner = move |name: &str, poller: &JsonPoller<T>| {
+            let on_data = on_data.clone();
+            let name = name.to_string();
+            poller.spawn(move |data, _| on_data(&name, data))
+        };
+        Self {
+            spawner: Box::new(spawner),
+            running: BTreeMap::new(),
+            max_in_flight: None,
+            limit: None,
+            drain: Duration::from_secs(5),
+            log: true,
+        }
+    }
+
+    // How long a stopped or reconfigured poller may take to finish its current cycle.
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain = timeout;
+        self
+    }
+
+    // Silences `watch_file`, like the poller setting of the same name; `apply` and `reload_from`
+    // still return every outcome.
+    pub fn internal_logging(mut self, enabled: bool) -> Self {
+        self.log = enabled;
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.running.keys().map(String::as_str)
+    }
+
+    // Every changed entry is built before anything is stopped, so an invalid config leaves the
+    // running set as it was.
+    pub async fn apply(&mut self, config: &GroupConfig) -> Result<ReloadSummary, ConfigError> {
+        let mut desired = BTreeMap::new();
+        for poller in &config.pollers {
+            let name = poller.resolved_name().to_string();
+            if desired.contains_key(&name) {
+                return Err(ConfigError::DuplicateName(name));
+            }
+            desired.insert(name, poller);
+        }
+
+        // A new group-wide limit needs a new limit, so every poller is restarted with it.
+        let limit_changed = config.max_in_flight != self.max_in_flight;
+        let limit = match limit_changed {
+            true => config.limit()?,
+            false => self.limit.clone(),
+        };
+        let mut built = Vec::new();
+        for (name, poller) in &desired {
+            match self.running.get(name) {
+                Some((current, _)) if !limit_changed && current == *poller => {}
+                _ => {
+                    let mut builder = poller.builder();
+                    if let Some(limit) = &limit {
+                        builder = builder.concurrency_limit(limit.clone());
+                    }
+                    built.push((name.clone(), (*poller).clone(), builder.build()?));
+                }
+            }
+        }
+        self.max_in_flight = config.max_in_flight;
+        self.limit = limit;
+
+        let mut summary = ReloadSummary::default();
+        let removed: Vec<_> = self
+            .running
+            .keys()
+            .filter(|name| !desired.contains_key(*name))
+            .cloned()
+            .collect();
+        for name in removed {
+            if let Some((_, task)) = self.running.remove(&name) {
+                let _ = task.shutdown(self.drain).await;
+            }
+            summary.stopped.push(name);
+        }
+
+        // Restarted pollers are spread out like a freshly built group, so a changed limit does
+        // not send every request at once.
+        let offsets = stagger_offsets(config.stagger(), built.iter().map(|(_, _, poller)| poller));
+        for ((name, config, poller), offset) in built.into_iter().zip(offsets) {
+            // The old loop stops first, so the two never poll or commit cursors side by side.
+            let reconfigured = match self.running.remove(&name) {
+                Some((_, previous)) => {
+                    let _ = previous.shutdown(self.drain).await;
+                    true
+                }
+                None => false,
+            };
+            let task = (self.spawner)(&name, &poller.delayed(offset)).abort_on_drop();
+            self.running.insert(name.clone(), (config, task));
+            match reconfigured {
+                true => summary.reconfigured.push(name),
+                false => summary.started.push(name),
+            }
+        }
+        Ok(summary)
+    }
+
+    pub async fn reload_from(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<ReloadSummary, ConfigError> {
+        self.apply(&GroupConfig::from_file(path)?).await
+    }
+
+    // Re-reads `path` every `every` and applies it whenever its contents change. Errors are
+    // logged (see `internal_logging`) and the previous set keeps running. Runs until the future
+    // is dropped.
+    pub async fn watch_file(&mut self, path: impl AsRef<Path>, every: Duration) {
+        let path = path.as_ref();
+        let mut last = None;
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            let owned = path.to_path_buf();
+            let read = tokio::task::spawn_blocking(move || std::fs::read_to_string(owned)).await;
+            let contents = match read.map_err(std::io::Error::other).and_then(|r| r) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    if self.log {
+                        tracing::warn!("Failed to read {}: {}", path.display(), e);
+                    }
+                    continue;
+                }
+            };
+            if last.as_ref() == Some(&contents) {
+                continue;
+            }
+            let result = match GroupConfig::from_json(&contents) {
+                Ok(config) => self.apply(&config).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(summary) if self.log && !summary.is_empty() => {
+                    tracing::info!("Reloaded {}: {:?}", path.display(), summary)
+                }
+                Err(e) if self.log => {
+                    tracing::warn!("Ignoring invalid config {}: {}", path.display(), e)
+                }
+                _ => {}
+            }
+            last = Some(contents);
+        }
+    }
+
+    pub async fn shutdown(self, timeout: Duration) {
+        let shutdowns = self
+            .running
+            .into_values()
+            .map(|(_, task)| task.shutdown(timeout));
+        futures_util::future::join_all(shutdowns).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, TestResponse};
+    use std::sync::{Arc, Mutex};
+
+    fn group(entries: &[(&str, &str, u64)]) -> GroupConfig {
+        let pollers = entries
+            .iter()
+            .map(|(name, url, interval)| PollerConfig {
+                name: Some(name.to_string()),
+                url: url.to_string(),
+                interval_ms: Some(*interval),
+                ..PollerConfig::default()
+            })
+            .collect();
+        GroupConfig {
+            pollers,
+            max_in_flight: None,
+            stagger_ms: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_diffs_running_set() {
+        let a = serve(|_, _| TestResponse::json("1")).await;
+        let b = serve(|_, _| TestResponse::json("2")).await;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let mut reloader = GroupReloader::new(move |name: &str, value: u64| {
+            recorder.lock().unwrap().push((name.to_string(), value));
+            async { Ok::<_, ()>(()) }
+        })
+        .drain_timeout(Duration::from_millis(100));
+
+        let summary = reloader.apply(&group(&[("a", &a.url, 10)])).await.unwrap();
+        assert_eq!(summary.started, ["a"]);
+
+        let summary = reloader.apply(&group(&[("a", &a.url, 10)])).await.unwrap();
+        assert!(summary.is_empty());
+
+        let summary = reloader
+            .apply(&group(&[("a", &a.url, 20), ("b", &b.url, 10)]))
+            .await
+            .unwrap();
+        assert_eq!(summary.started, ["b"]);
+        assert_eq!(summary.reconfigured, ["a"]);
+
+        let invalid = reloader.apply(&group(&[("b", &b.url, 0)])).await;
+        assert!(invalid.is_err());
+        assert_eq!(reloader.names().collect::<Vec<_>>(), ["a", "b"]);
+
+        let summary = reloader.apply(&group(&[("b", &b.url, 10)])).await.unwrap();
+        assert_eq!(summary.stopped, ["a"]);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let hits = a.hits();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(a.hits(), hits);
+        assert!(seen.lock().unwrap().contains(&("b".to_string(), 2)));
+
+        reloader.shutdown(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_reload_staggers_restarted_pollers() {
+        let a = serve(|_, _| TestResponse::json("1")).await;
+        let b = serve(|_, _| TestResponse::json("2")).await;
+        let mut reloader = GroupReloader::new(|_: &str, _: u64| async { Ok::<_, ()>(()) })
+            .drain_timeout(Duration::from_millis(100));
+        let mut config = group(&[("a", &a.url, 400), ("b", &b.url, 400)]);
+        reloader.apply(&config).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!((a.hits(), b.hits()), (1, 0));
+
+        // A new limit restarts both, again half an interval apart.
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        config.max_in_flight = Some(2);
+        let summary = reloader.apply(&config).await.unwrap();
+        assert_eq!(summary.reconfigured, ["a", "b"]);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!((a.hits(), b.hits()), (2, 1));
+
+        reloader.shutdown(Duration::from_millis(100)).await;
+    }
+}
@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheBuster {
+    Timestamp,
+    Nonce,
+}
+
+pub(crate) struct CacheBust {
+    param: String,
+    kind: CacheBuster,
+    state: RandomState,
+    sequence: AtomicU64,
+}
+
+impl CacheBust {
+    pub(crate) fn new(param: String, kind: CacheBuster) -> Self {
+        Self {
+            param,
+            kind,
+            state: RandomState::new(),
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn param(&self) -> &str {
+        &self.param
+    }
+
+    pub(crate) fn next_value(&self) -> String {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        match self.kind {
+            CacheBuster::Timestamp => {
+                let millis = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_millis());
+                // The sequence keeps values unique when two polls land in the same millisecond.
+                format!("{millis}{sequence:03}")
+            }
+            CacheBuster::Nonce => format!("{:016x}", self.state.hash_one(sequence)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestId {
+    UuidV4,
+    // Time-ordered, so IDs sort by when the request was sent.
+    UuidV7,
+}
+
+pub(crate) struct RequestIds {
+    header: String,
+    kind: RequestId,
+    state: RandomState,
+    sequence: AtomicU64,
+}
+
+impl RequestIds {
+    pub(crate) fn new(header: String, kind: RequestId) -> Self {
+        Self {
+            header,
+            kind,
+            state: RandomState::new(),
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn header(&self) -> &str {
+        &self.header
+    }
+
+    pub(crate) fn next_value(&self) -> String {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let high = self.state.hash_one((sequence, 0u8)).to_be_bytes();
+        let low = self.state.hash_one((sequence, 1u8)).to_be_bytes();
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&high);
+        bytes[8..].copy_from_slice(&low);
+
+        let version = match self.kind {
+            RequestId::UuidV4 => 0x40,
+            RequestId::UuidV7 => {
+                let millis = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_millis() as u64);
+                bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+                0x70
+            }
+        };
+        bytes[6] = bytes[6] & 0x0f | version;
+        bytes[8] = bytes[8] & 0x3f | 0x80;
+
+        let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+}
+
+tokio::task_local! {
+    static SENT_URL: RefCell<Option<String>>;
+}
+
+// Runs `fetch` and returns the URL its primary request was last sent to, with the query built from
+// templates, cache busting and the resume token.
+pub(crate) async fn sent_url<F: Future>(fetch: F) -> (F::Output, Option<String>) {
+    SENT_URL
+        .scope(RefCell::new(None), async {
+            let output = fetch.await;
+            (output, SENT_URL.with(RefCell::take))
+        })
+        .await
+}
+
+pub(crate) fn record_sent_url(url: &reqwest::Url) {
+    let _ = SENT_URL.try_with(|sent| *sent.borrow_mut() = Some(url.to_string()));
+}
+
+// Appends the request ID to logged errors so they can be matched with server-side logs.
+pub(crate) struct WithRequestId<'a, E>(pub(crate) &'a E, pub(crate) Option<&'a str>);
+
+impl<E: fmt::Debug> fmt::Debug for WithRequestId<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(id) => write!(f, "{:?} (request id {})", self.0, id),
+            None => write!(f, "{:?}", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_are_unique_per_request() {
+        for kind in [CacheBuster::Timestamp, CacheBuster::Nonce] {
+            let bust = CacheBust::new("_".into(), kind);
+            let first = bust.next_value();
+            let second = bust.next_value();
+            assert_ne!(first, second);
+        }
+    }
+
+    #[test]
+    fn test_request_ids_are_uuids() {
+        let v4 = RequestIds::new("X-Request-Id".into(), RequestId::UuidV4);
+        let first = v4.next_value();
+        assert_eq!(first.len(), 36);
+        assert_eq!(&first[14..15], "4");
+        assert!(matches!(&first[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(first, v4.next_value());
+
+        let v7 = RequestIds::new("X-Request-Id".into(), RequestId::UuidV7);
+        let (a, b) = (v7.next_value(), v7.next_value());
+        assert_eq!(&a[14..15], "7");
+        assert_eq!(&a[..8], &b[..8]);
+    }
+}
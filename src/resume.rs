@@ -0,0 +1,41 @@
+use std::sync::{Arc, RwLock};
+
+pub(crate) type ResumeFn<T> = Arc<dyn Fn(&T) -> Option<String> + Send + Sync>;
+
+// The latest bookmark (e.g. a Kubernetes `resourceVersion`), sent as a query parameter so the
+// server only returns what changed since.
+pub(crate) struct ResumeToken {
+    param: String,
+    current: RwLock<Option<String>>,
+}
+
+impl ResumeToken {
+    pub(crate) fn new(param: String) -> Self {
+        Self {
+            param,
+            current: RwLock::new(None),
+        }
+    }
+
+    pub(crate) fn param(&self) -> &str {
+        &self.param
+    }
+
+    pub(crate) fn current(&self) -> Option<String> {
+        self.current.read().unwrap().clone()
+    }
+
+    pub(crate) fn update(&self, token: String) {
+        *self.current.write().unwrap() = Some(token);
+    }
+
+    // Puts back the token a poll started from, when its payload was not committed.
+    pub(crate) fn rewind(&self, token: Option<String>) {
+        *self.current.write().unwrap() = token;
+    }
+
+    // Returns whether there was a token to drop.
+    pub(crate) fn clear(&self) -> bool {
+        self.current.write().unwrap().take().is_some()
+    }
+}
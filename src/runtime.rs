@@ -0,0 +1,57 @@
+use std::io;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Handle};
+use tokio::sync::oneshot;
+
+// Where `spawn` (and everything built on it) runs the polling loop. By default that is the
+// runtime the caller is on.
+#[derive(Clone)]
+pub(crate) enum PollRuntime {
+    Current,
+    Handle(Handle),
+    Dedicated(Arc<DedicatedRuntime>),
+}
+
+impl PollRuntime {
+    pub(crate) fn handle(&self) -> Handle {
+        match self {
+            Self::Current => Handle::current(),
+            Self::Handle(handle) => handle.clone(),
+            Self::Dedicated(runtime) => runtime.handle.clone(),
+        }
+    }
+}
+
+// A single-threaded runtime on its own OS thread, kept alive for as long as a poller (or one of
+// its clones, including the one each spawned loop owns) refers to it.
+pub(crate) struct DedicatedRuntime {
+    handle: Handle,
+    stop: Option<oneshot::Sender<()>>,
+}
+
+impl DedicatedRuntime {
+    pub(crate) fn start(name: &str) -> io::Result<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let handle = runtime.handle().clone();
+        let (stop, stopped) = oneshot::channel::<()>();
+        std::thread::Builder::new()
+            .name(format!("json-poller {name}"))
+            .spawn(move || {
+                runtime.block_on(async {
+                    let _ = stopped.await;
+                })
+            })?;
+        Ok(Self {
+            handle,
+            stop: Some(stop),
+        })
+    }
+}
+
+impl Drop for DedicatedRuntime {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
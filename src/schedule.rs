@@ -0,0 +1,175 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    pub const WORKDAYS: [Weekday; 5] = [Self::Mon, Self::Tue, Self::Wed, Self::Thu, Self::Fri];
+
+    pub(crate) fn mask(days: &[Weekday]) -> u8 {
+        days.iter().fold(0, |mask, day| mask | 1 << *day as u8)
+    }
+}
+
+// A fixed offset from UTC. Daylight-saving transitions are not applied automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UtcOffset(i32);
+
+impl UtcOffset {
+    pub const UTC: Self = Self(0);
+
+    pub fn hours(hours: i32) -> Self {
+        Self(hours * 3600)
+    }
+
+    pub fn minutes(minutes: i32) -> Self {
+        Self(minutes * 60)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ActiveHours {
+    start: i64,
+    end: i64,
+    offset: i64,
+    days: u8,
+}
+
+impl ActiveHours {
+    pub(crate) fn new(
+        window: Option<(&str, &str)>,
+        offset: UtcOffset,
+        days: u8,
+    ) -> Result<Self, String> {
+        let (start, end) = match window {
+            Some((start, end)) => (parse_time(start)?, parse_time(end)?),
+            None => (0, DAY),
+        };
+        if start == end {
+            return Err("active_between window is empty".into());
+        }
+        if days == 0 {
+            return Err("active_days must include at least one day".into());
+        }
+        Ok(Self {
+            start,
+            end,
+            offset: offset.0.into(),
+            days,
+        })
+    }
+
+    // None while inside the window, otherwise how long until it opens again. A window whose end
+    // is before its start runs past midnight and belongs to the day it started on.
+    pub(crate) fn until_active(&self, now: SystemTime) -> Option<Duration> {
+        let since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let local = since_epoch.as_secs() as i64 + self.offset;
+        let day = local.div_euclid(DAY);
+        let time = local.rem_euclid(DAY);
+
+        let active = if self.start < self.end {
+            self.runs_on(day) && (self.start..self.end).contains(&time)
+        } else {
+            (time >= self.start && self.runs_on(day)) || (time < self.end && self.runs_on(day - 1))
+        };
+        if active {
+            return None;
+        }
+
+        let opens = (0..=7)
+            .map(|d| (day + d) * DAY + self.start)
+            .find(|&opens| opens > local && self.runs_on(opens.div_euclid(DAY)))?;
+        let wait = Duration::from_secs((opens - local) as u64);
+        Some(wait.saturating_sub(Duration::from_nanos(since_epoch.subsec_nanos().into())))
+    }
+
+    fn runs_on(&self, day: i64) -> bool {
+        // 1970-01-01 was a Thursday.
+        let weekday = (day + 3).rem_euclid(7);
+        self.days & (1 << weekday) != 0
+    }
+}
+
+fn parse_time(time: &str) -> Result<i64, String> {
+    let invalid = || format!("`{time}` is not a time of day (HH:MM or HH:MM:SS)");
+    let mut parts = time.trim().split(':').map(|part| part.parse::<i64>().ok());
+    let hours = parts.next().flatten().ok_or_else(invalid)?;
+    let minutes = parts.next().flatten().ok_or_else(invalid)?;
+    let seconds = match parts.next() {
+        Some(seconds) => seconds.ok_or_else(invalid)?,
+        None => 0,
+    };
+    let secs = hours * 3600 + minutes * 60 + seconds;
+    if parts.next().is_some() || !(0..60).contains(&minutes) || !(0..60).contains(&seconds) {
+        return Err(invalid());
+    }
+    if !(0..=DAY).contains(&secs) {
+        return Err(invalid());
+    }
+    Ok(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Monday 2024-01-01 00:00 UTC.
+    const MONDAY: u64 = 1_704_067_200;
+
+    fn at(day: u64, hours: u64, minutes: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(MONDAY + day * DAY as u64 + hours * 3600 + minutes * 60)
+    }
+
+    #[test]
+    fn test_business_hours_on_workdays() {
+        let days = Weekday::mask(&Weekday::WORKDAYS);
+        let hours = ActiveHours::new(Some(("09:00", "17:30")), UtcOffset::UTC, days).unwrap();
+
+        assert_eq!(hours.until_active(at(0, 9, 0)), None);
+        assert_eq!(hours.until_active(at(0, 17, 29)), None);
+        assert_eq!(
+            hours.until_active(at(0, 17, 30)),
+            Some(Duration::from_secs(15 * 3600 + 30 * 60))
+        );
+        // Friday evening waits for Monday morning.
+        assert_eq!(
+            hours.until_active(at(4, 18, 0)),
+            Some(Duration::from_secs((2 * 24 + 15) * 3600))
+        );
+    }
+
+    #[test]
+    fn test_offset_and_overnight_window() {
+        let all = Weekday::mask(&[Weekday::Mon]);
+        let hours = ActiveHours::new(Some(("22:00", "02:00")), UtcOffset::hours(2), all).unwrap();
+
+        // 20:30 UTC is 22:30 local on Monday.
+        assert_eq!(hours.until_active(at(0, 20, 30)), None);
+        // 23:30 UTC is 01:30 local on Tuesday, still part of Monday's window.
+        assert_eq!(hours.until_active(at(0, 23, 30)), None);
+        assert_eq!(
+            hours.until_active(at(1, 0, 30)),
+            Some(Duration::from_secs(6 * 24 * 3600 + 19 * 3600 + 30 * 60))
+        );
+    }
+
+    #[test]
+    fn test_invalid_windows_are_rejected() {
+        let all = Weekday::mask(&[Weekday::Mon]);
+        assert!(ActiveHours::new(Some(("9", "17:00")), UtcOffset::UTC, all).is_err());
+        assert!(ActiveHours::new(Some(("09:60", "17:00")), UtcOffset::UTC, all).is_err());
+        assert!(ActiveHours::new(Some(("09:00", "09:00")), UtcOffset::UTC, all).is_err());
+        assert!(ActiveHours::new(None, UtcOffset::UTC, 0).is_err());
+        assert!(ActiveHours::new(Some(("00:00", "24:00")), UtcOffset::UTC, all).is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::seed::Decoder;
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Where a payload states its schema version.
+#[derive(Debug, Clone)]
+pub(crate) enum VersionSource {
+    Header(String),
+    // JSON pointer into the body.
+    Field(String),
+}
+
+// Decodes a response body, through the version-specific decoders when any are configured.
+pub(crate) fn decode<T>(
+    latest: &Decoder<T>,
+    schemas: Option<&Schemas<T>>,
+    headers: &HeaderMap,
+    body: &[u8],
+    unknown: Option<&mut Vec<String>>,
+) -> serde_json::Result<T> {
+    match schemas {
+        Some(schemas) => schemas.decode(latest, headers, body, unknown),
+        None => latest.decode_body(body, unknown),
+    }
+}
+
+// Decoders for older schema versions, each upgrading to `T`. Payloads without a version, or with
+// one that isn't registered, are decoded as the latest `T`.
+pub(crate) struct Schemas<T> {
+    source: VersionSource,
+    versions: HashMap<String, Decoder<T>>,
+}
+
+impl<T> Schemas<T> {
+    pub(crate) fn new(source: VersionSource, versions: HashMap<String, Decoder<T>>) -> Self {
+        Self { source, versions }
+    }
+
+    pub(crate) fn decode(
+        &self,
+        latest: &Decoder<T>,
+        headers: &HeaderMap,
+        body: &[u8],
+        unknown: Option<&mut Vec<String>>,
+    ) -> serde_json::Result<T> {
+        match &self.source {
+            VersionSource::Header(name) => {
+                let version = headers.get(name).and_then(|v| v.to_str().ok());
+                self.decoder(version, latest).decode_body(body, unknown)
+            }
+            VersionSource::Field(pointer) => {
+                let value: Value = serde_json::from_slice(body)?;
+                let version = match value.pointer(pointer) {
+                    Some(Value::String(version)) => Some(version.clone()),
+                    Some(Value::Number(version)) => Some(version.to_string()),
+                    _ => None,
+                };
+                self.decoder(version.as_deref(), latest)
+                    .decode_checked(value, unknown)
+            }
+        }
+    }
+
+    fn decoder<'a>(&'a self, version: Option<&str>, latest: &'a Decoder<T>) -> &'a Decoder<T> {
+        version
+            .and_then(|version| self.versions.get(version.trim()))
+            .unwrap_or(latest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Price {
+        cents: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct PriceV1 {
+        dollars: f64,
+    }
+
+    fn versions() -> HashMap<String, Decoder<Price>> {
+        let upgrade = |old: PriceV1| Price {
+            cents: (old.dollars * 100.0).round() as u64,
+        };
+        HashMap::from([("1".to_string(), Decoder::upgrading(upgrade))])
+    }
+
+    #[test]
+    fn test_version_from_header_picks_the_decoder() {
+        let schemas = Schemas::new(VersionSource::Header("x-schema".into()), versions());
+        let latest = Decoder::owned();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-schema", "1".parse().unwrap());
+        let old = schemas.decode(&latest, &headers, br#"{"dollars":1.25}"#, None);
+        assert_eq!(old.unwrap(), Price { cents: 125 });
+
+        headers.insert("x-schema", "2".parse().unwrap());
+        let current = schemas.decode(&latest, &headers, br#"{"cents":7}"#, None);
+        assert_eq!(current.unwrap(), Price { cents: 7 });
+    }
+
+    #[test]
+    fn test_version_from_numeric_field() {
+        let schemas = Schemas::new(VersionSource::Field("/meta/v".into()), versions());
+        let latest = Decoder::owned();
+        let headers = HeaderMap::new();
+        let old = schemas.decode(&latest, &headers, br#"{"meta":{"v":1},"dollars":2}"#, None);
+        assert_eq!(old.unwrap(), Price { cents: 200 });
+        let unversioned = schemas.decode(&latest, &headers, br#"{"cents":3}"#, None);
+        assert_eq!(unversioned.unwrap(), Price { cents: 3 });
+    }
+}
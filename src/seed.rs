@@ -0,0 +1,188 @@
+use crate::strict::Tracked;
+use crate::PollBody;
+use serde::de::{DeserializeOwned, DeserializeSeed, Error as _};
+use serde_json::Value;
+use std::sync::Arc;
+
+// Payload types `JsonPoller::builder` can decode without a seed: everything `DeserializeOwned`,
+// plus `PollBody`, which only the raw fetches produce.
+pub trait Payload: Sized {
+    #[doc(hidden)]
+    fn from_json(body: &[u8]) -> serde_json::Result<Self>;
+    #[doc(hidden)]
+    fn from_json_value(value: Value) -> serde_json::Result<Self>;
+    // Like `from_json_value`, collecting the pointers of fields `Self` does not declare.
+    #[doc(hidden)]
+    fn from_json_checked(value: Value, unknown: &mut Vec<String>) -> serde_json::Result<Self>;
+}
+
+impl<T: DeserializeOwned> Payload for T {
+    fn from_json(body: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(body)
+    }
+
+    fn from_json_value(value: Value) -> serde_json::Result<Self> {
+        serde_json::from_value(value)
+    }
+
+    fn from_json_checked(value: Value, unknown: &mut Vec<String>) -> serde_json::Result<Self> {
+        T::deserialize(Tracked::new(value, unknown))
+    }
+}
+
+fn raw_only() -> serde_json::Error {
+    serde_json::Error::custom("use fetch_raw or start_raw for PollBody")
+}
+
+impl Payload for PollBody {
+    fn from_json(_: &[u8]) -> serde_json::Result<Self> {
+        Err(raw_only())
+    }
+
+    fn from_json_value(_: Value) -> serde_json::Result<Self> {
+        Err(raw_only())
+    }
+
+    fn from_json_checked(_: Value, _: &mut Vec<String>) -> serde_json::Result<Self> {
+        Err(raw_only())
+    }
+}
+
+type FromSlice<T> = Arc<dyn Fn(&[u8]) -> serde_json::Result<T> + Send + Sync>;
+type FromValue<T> = Arc<dyn Fn(Value) -> serde_json::Result<T> + Send + Sync>;
+type FromChecked<T> = Arc<dyn Fn(Value, &mut Vec<String>) -> serde_json::Result<T> + Send + Sync>;
+
+// Turns response bodies (or materialized delta documents) into `T`: plain `Deserialize` by
+// default, or through a fresh `DeserializeSeed` per payload with `builder_with_seed`.
+pub(crate) struct Decoder<T> {
+    from_slice: FromSlice<T>,
+    from_value: FromValue<T>,
+    from_checked: FromChecked<T>,
+}
+
+impl<T: 'static> Decoder<T> {
+    pub(crate) fn owned() -> Self
+    where
+        T: Payload,
+    {
+        Self {
+            from_slice: Arc::new(T::from_json),
+            from_value: Arc::new(T::from_json_value),
+            from_checked: Arc::new(T::from_json_checked),
+        }
+    }
+
+    pub(crate) fn seeded<F, S>(factory: F) -> Self
+    where
+        F: Fn() -> S + Send + Sync + 'static,
+        S: for<'de> DeserializeSeed<'de, Value = T>,
+    {
+        let factory = Arc::new(factory);
+        let (seed, checked) = (factory.clone(), factory.clone());
+        Self {
+            from_slice: Arc::new(move |body| {
+                let mut deserializer = serde_json::Deserializer::from_slice(body);
+                let data = seed().deserialize(&mut deserializer)?;
+                deserializer.end()?;
+                Ok(data)
+            }),
+            from_value: Arc::new(move |value| factory().deserialize(value)),
+            from_checked: Arc::new(move |value, unknown| {
+                checked().deserialize(Tracked::new(value, unknown))
+            }),
+        }
+    }
+
+    // Decodes as `V` and converts the result, for older schema versions.
+    pub(crate) fn upgrading<V, F>(upgrade: F) -> Self
+    where
+        V: DeserializeOwned + 'static,
+        F: Fn(V) -> T + Send + Sync + 'static,
+    {
+        let upgrade = Arc::new(upgrade);
+        let (owned, checked) = (upgrade.clone(), upgrade.clone());
+        Self {
+            from_slice: Arc::new(move |body| serde_json::from_slice(body).map(|v| upgrade(v))),
+            from_value: Arc::new(move |value| serde_json::from_value(value).map(|v| owned(v))),
+            from_checked: Arc::new(move |value, unknown| {
+                V::deserialize(Tracked::new(value, unknown)).map(|v| checked(v))
+            }),
+        }
+    }
+}
+
+impl<T> Decoder<T> {
+    pub(crate) fn decode(&self, body: &[u8]) -> serde_json::Result<T> {
+        (self.from_slice)(body)
+    }
+
+    pub(crate) fn decode_value(&self, value: Value) -> serde_json::Result<T> {
+        (self.from_value)(value)
+    }
+
+    // With `unknown_fields` the body is parsed into a `Value` first so skipped fields can be
+    // collected on the way into `T`.
+    pub(crate) fn decode_checked(
+        &self,
+        value: Value,
+        unknown: Option<&mut Vec<String>>,
+    ) -> serde_json::Result<T> {
+        match unknown {
+            Some(unknown) => (self.from_checked)(value, unknown),
+            None => self.decode_value(value),
+        }
+    }
+
+    pub(crate) fn decode_body(
+        &self,
+        body: &[u8],
+        unknown: Option<&mut Vec<String>>,
+    ) -> serde_json::Result<T> {
+        match unknown {
+            Some(unknown) => (self.from_checked)(serde_json::from_slice(body)?, unknown),
+            None => self.decode(body),
+        }
+    }
+}
+
+impl<T> Clone for Decoder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            from_slice: self.from_slice.clone(),
+            from_value: self.from_value.clone(),
+            from_checked: self.from_checked.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Deserializer};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    // Interns every string it sees, handing out indices instead.
+    struct Interner(Arc<Mutex<HashMap<String, usize>>>);
+
+    impl<'de> DeserializeSeed<'de> for Interner {
+        type Value = usize;
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+            let name = String::deserialize(deserializer)?;
+            let mut names = self.0.lock().unwrap();
+            let next = names.len();
+            Ok(*names.entry(name).or_insert(next))
+        }
+    }
+
+    #[test]
+    fn test_seed_is_created_per_payload() {
+        let names = Arc::default();
+        let decoder = Decoder::seeded(move || Interner(Arc::clone(&names)));
+        assert_eq!(decoder.decode(br#""eur""#).unwrap(), 0);
+        assert_eq!(decoder.decode(br#""usd""#).unwrap(), 1);
+        assert_eq!(decoder.decode_value(Value::from("eur")).unwrap(), 0);
+        assert!(decoder.decode(br#""eur" trailing"#).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{JsonPoller, PollError};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PollRequest {
+    pub tick: u64,
+}
+
+impl PollRequest {
+    pub fn new(tick: u64) -> Self {
+        Self { tick }
+    }
+}
+
+// One call is one fetch of the configured URL(s), so tower middleware (retry, timeout,
+// load-shed, rate-limit) can be layered around the poller like any other service.
+impl<T> Service<PollRequest> for JsonPoller<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    type Response = T;
+    type Error = PollError;
+    type Future = Pin<Box<dyn Future<Output = Result<T, PollError>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _request: PollRequest) -> Self::Future {
+        let poller = self.clone();
+        Box::pin(async move { poller.fetch_once().await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, TestResponse};
+
+    #[tokio::test]
+    async fn test_service_call_fetches_once() {
+        let server = serve(|index, _| TestResponse::json(format!("{index}"))).await;
+        let mut poller = JsonPoller::<u64>::builder(&server.url).build().unwrap();
+
+        std::future::poll_fn(|cx| poller.poll_ready(cx))
+            .await
+            .unwrap();
+        assert_eq!(poller.call(PollRequest::new(1)).await.unwrap(), 0);
+        assert_eq!(poller.call(PollRequest::new(2)).await.unwrap(), 1);
+    }
+}
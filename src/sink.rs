@@ -0,0 +1,66 @@
+use crate::PollMeta;
+use futures_util::{Sink, SinkExt};
+use std::convert::Infallible;
+use std::future::Future;
+use tokio::sync::{broadcast, mpsc, watch};
+
+pub trait PollSink<T>: Send {
+    type Error: std::fmt::Debug;
+
+    fn deliver(
+        &mut self,
+        item: T,
+        meta: PollMeta,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+// A closed channel stops the poller: nobody is left to consume what it fetches.
+impl<T: Send> PollSink<T> for mpsc::Sender<T> {
+    type Error = mpsc::error::SendError<T>;
+
+    async fn deliver(&mut self, item: T, _meta: PollMeta) -> Result<(), Self::Error> {
+        self.send(item).await
+    }
+}
+
+impl<T: Send> PollSink<T> for mpsc::UnboundedSender<T> {
+    type Error = mpsc::error::SendError<T>;
+
+    async fn deliver(&mut self, item: T, _meta: PollMeta) -> Result<(), Self::Error> {
+        self.send(item)
+    }
+}
+
+// Watch and broadcast receivers may come and go, so having none right now is not an error.
+impl<T: Send + Sync> PollSink<T> for watch::Sender<T> {
+    type Error = Infallible;
+
+    async fn deliver(&mut self, item: T, _meta: PollMeta) -> Result<(), Self::Error> {
+        self.send_replace(item);
+        Ok(())
+    }
+}
+
+impl<T: Send> PollSink<T> for broadcast::Sender<T> {
+    type Error = Infallible;
+
+    async fn deliver(&mut self, item: T, _meta: PollMeta) -> Result<(), Self::Error> {
+        let _ = self.send(item);
+        Ok(())
+    }
+}
+
+pub struct FuturesSink<S>(pub S);
+
+impl<T, S> PollSink<T> for FuturesSink<S>
+where
+    T: Send,
+    S: Sink<T> + Unpin + Send,
+    S::Error: std::fmt::Debug,
+{
+    type Error = S::Error;
+
+    async fn deliver(&mut self, item: T, _meta: PollMeta) -> Result<(), Self::Error> {
+        self.0.send(item).await
+    }
+}
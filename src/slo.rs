@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// A latency objective such as "p99 below 300 ms over 5 minutes", checked against successful
+// polls. `quantile` is a fraction: 0.99 for p99.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencySlo {
+    quantile: f64,
+    threshold: Duration,
+    window: Duration,
+    min_samples: usize,
+}
+
+impl LatencySlo {
+    pub fn new(quantile: f64, threshold: Duration, window: Duration) -> Self {
+        Self {
+            quantile,
+            threshold,
+            window,
+            min_samples: 10,
+        }
+    }
+
+    // Polls the window needs before the percentile is judged at all (default: 10).
+    pub fn min_samples(mut self, samples: usize) -> Self {
+        self.min_samples = samples.max(1);
+        self
+    }
+
+    pub fn quantile(&self) -> f64 {
+        self.quantile
+    }
+
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !(self.quantile > 0.0 && self.quantile <= 1.0) {
+            return Err(format!("SLO quantile {} is not in (0, 1]", self.quantile));
+        }
+        if self.window.is_zero() {
+            return Err("SLO window must not be zero".into());
+        }
+        Ok(())
+    }
+}
+
+pub(crate) enum SloChange {
+    Breached(Duration),
+    Recovered(Duration),
+}
+
+// Latencies within the window, and whether the last verdict was a breach, so each breach and
+// recovery is reported once.
+pub(crate) struct SloTracker {
+    slo: LatencySlo,
+    state: Mutex<(VecDeque<(Instant, Duration)>, bool)>,
+}
+
+impl SloTracker {
+    pub(crate) fn new(slo: LatencySlo) -> Self {
+        Self {
+            slo,
+            state: Mutex::new((VecDeque::new(), false)),
+        }
+    }
+
+    pub(crate) fn slo(&self) -> &LatencySlo {
+        &self.slo
+    }
+
+    pub(crate) fn observe(&self, now: Instant, latency: Duration) -> Option<SloChange> {
+        let mut state = self.state.lock().unwrap();
+        let (samples, breached) = &mut *state;
+        samples.push_back((now, latency));
+        while samples
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > self.slo.window)
+        {
+            samples.pop_front();
+        }
+        if samples.len() < self.slo.min_samples {
+            return None;
+        }
+        let mut latencies: Vec<_> = samples.iter().map(|(_, latency)| *latency).collect();
+        latencies.sort_unstable();
+        // Nearest rank: the smallest latency that at least `quantile` of the polls stayed within.
+        let rank = (self.slo.quantile * latencies.len() as f64).ceil() as usize;
+        let observed = latencies[rank.clamp(1, latencies.len()) - 1];
+        match (observed > self.slo.threshold, *breached) {
+            (true, false) => {
+                *breached = true;
+                Some(SloChange::Breached(observed))
+            }
+            (false, true) => {
+                *breached = false;
+                Some(SloChange::Recovered(observed))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breach_and_recovery_are_reported_once() {
+        let ms = Duration::from_millis;
+        let slo = LatencySlo::new(0.9, ms(300), Duration::from_secs(60)).min_samples(10);
+        let tracker = SloTracker::new(slo);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        for i in 0..9 {
+            assert!(tracker.observe(at(i), ms(100)).is_none());
+        }
+        // One slow poll out of ten is still within p90.
+        assert!(tracker.observe(at(9), ms(900)).is_none());
+        assert!(
+            matches!(tracker.observe(at(10), ms(500)), Some(SloChange::Breached(p)) if p == ms(500))
+        );
+        assert!(tracker.observe(at(11), ms(800)).is_none());
+        // Once the slow polls have left the window, the percentile recovers.
+        assert!(tracker.observe(at(80), ms(100)).is_none());
+        let recovered = (81..90).find_map(|i| tracker.observe(at(i), ms(100)));
+        assert!(matches!(recovered, Some(SloChange::Recovered(p)) if p == ms(100)));
+        assert!(LatencySlo::new(99.0, ms(1), ms(1)).validate().is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::sink::PollSink;
+use crate::PollMeta;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Appends one row per poll to `table` (default `polls`): `at` (unix ms), `tick`, `status`,
+// `latency_ms` and the payload as JSON text in `payload`. The table is created on the first
+// delivery if it doesn't exist, and inserts run on the blocking pool.
+pub struct SqliteSink {
+    connection: Arc<Mutex<Connection>>,
+    table: String,
+    created: bool,
+}
+
+impl SqliteSink {
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Ok(Self::new(Connection::open(path)?))
+    }
+
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection: Arc::new(Mutex::new(connection)),
+            table: "polls".into(),
+            created: false,
+        }
+    }
+
+    pub fn table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self.created = false;
+        self
+    }
+
+    // Runs `f` against the connection, e.g. to query what was recorded so far.
+    pub fn with_connection<R>(&self, f: impl FnOnce(&Connection) -> R) -> R {
+        f(&self.connection.lock().unwrap())
+    }
+}
+
+impl<T: Serialize + Send> PollSink<T> for SqliteSink {
+    type Error = rusqlite::Error;
+
+    async fn deliver(&mut self, item: T, meta: PollMeta) -> Result<(), Self::Error> {
+        let payload = serde_json::to_string(&item)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        let table = quote(&self.table);
+        let create = (!self.created).then(|| {
+            format!(
+                "CREATE TABLE IF NOT EXISTS {table} (at INTEGER NOT NULL, tick INTEGER NOT NULL, \
+                 status INTEGER, latency_ms INTEGER NOT NULL, payload TEXT NOT NULL)"
+            )
+        });
+        let insert = format!(
+            "INSERT INTO {table} (at, tick, status, latency_ms, payload) VALUES (?1, ?2, ?3, ?4, ?5)"
+        );
+        let row = (
+            at,
+            meta.tick as i64,
+            meta.status.map(|status| status.as_u16()),
+            meta.elapsed.as_millis() as i64,
+            payload,
+        );
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let connection = connection.lock().unwrap();
+            if let Some(create) = create {
+                connection.execute(&create, ())?;
+            }
+            connection.execute(&insert, row).map(drop)
+        })
+        .await
+        .expect("sqlite sink write panicked")?;
+        self.created = true;
+        Ok(())
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_appends_a_row_per_poll() {
+        let mut sink = SqliteSink::new(Connection::open_in_memory().unwrap()).table("prices");
+        let meta = PollMeta {
+            status: Some(StatusCode::OK),
+            elapsed: Duration::from_millis(12),
+            ..PollMeta::new(1)
+        };
+        sink.deliver(serde_json::json!({"a": 1}), meta)
+            .await
+            .unwrap();
+        sink.deliver(serde_json::json!({"a": 2}), PollMeta::new(2))
+            .await
+            .unwrap();
+
+        let rows: Vec<(i64, Option<u16>, i64, String)> = sink.with_connection(|connection| {
+            let mut query = connection
+                .prepare("SELECT tick, status, latency_ms, payload FROM prices ORDER BY tick")
+                .unwrap();
+            query
+                .query_map((), |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        });
+        assert_eq!(
+            rows,
+            [
+                (1, Some(200), 12, r#"{"a":1}"#.to_string()),
+                (2, None, 0, r#"{"a":2}"#.to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+pub(crate) struct StaleWatchdog {
+    window: Duration,
+    unchanged_is_stale: bool,
+    last_fresh: Instant,
+    last_hash: Option<u64>,
+    stale: bool,
+}
+
+impl StaleWatchdog {
+    pub(crate) fn new(window: Duration, unchanged_is_stale: bool, now: Instant) -> Self {
+        Self {
+            window,
+            unchanged_is_stale,
+            last_fresh: now,
+            last_hash: None,
+            stale: false,
+        }
+    }
+
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        (!self.stale).then(|| self.last_fresh + self.window)
+    }
+
+    pub(crate) fn record_success(&mut self, now: Instant, digest: u64) {
+        if self.unchanged_is_stale && self.last_hash.replace(digest) == Some(digest) {
+            return;
+        }
+        self.touch(now);
+    }
+
+    pub(crate) fn touch(&mut self, now: Instant) {
+        self.last_fresh = now;
+        self.stale = false;
+    }
+
+    pub(crate) fn check(&mut self, now: Instant) -> Option<Duration> {
+        let age = now.saturating_duration_since(self.last_fresh);
+        if self.stale || age < self.window {
+            return None;
+        }
+        self.stale = true;
+        Some(age)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::change::fnv1a;
+
+    const WINDOW: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_fires_once_after_window_without_success() {
+        let start = Instant::now();
+        let mut watchdog = StaleWatchdog::new(WINDOW, false, start);
+
+        assert_eq!(watchdog.check(start + Duration::from_secs(9)), None);
+        assert_eq!(watchdog.check(start + WINDOW), Some(WINDOW));
+        assert_eq!(watchdog.check(start + Duration::from_secs(20)), None);
+        assert_eq!(watchdog.deadline(), None);
+    }
+
+    #[test]
+    fn test_success_resets_window() {
+        let start = Instant::now();
+        let mut watchdog = StaleWatchdog::new(WINDOW, false, start);
+
+        watchdog.record_success(start + Duration::from_secs(8), fnv1a(b"{}"));
+        assert_eq!(watchdog.check(start + Duration::from_secs(15)), None);
+        assert_eq!(watchdog.deadline(), Some(start + Duration::from_secs(18)));
+    }
+
+    #[test]
+    fn test_unchanged_payload_does_not_count_as_fresh() {
+        let start = Instant::now();
+        let mut watchdog = StaleWatchdog::new(WINDOW, true, start);
+
+        watchdog.record_success(start + Duration::from_secs(1), fnv1a(b"{\"a\":1}"));
+        watchdog.record_success(start + Duration::from_secs(9), fnv1a(b"{\"a\":1}"));
+        assert!(watchdog.check(start + Duration::from_secs(11)).is_some());
+
+        watchdog.record_success(start + Duration::from_secs(12), fnv1a(b"{\"a\":2}"));
+        assert_eq!(watchdog.deadline(), Some(start + Duration::from_secs(22)));
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::Display;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+// Where and how `report_statsd` pushes per-poll metrics. Metrics are sent as
+// `{prefix}.fetch.success`, `.fetch.errors`, `.fetch.duration`, `.received_bytes`,
+// `.received_wire_bytes`, `.payload_bytes`, `.parse_time` and `.queue_depth`, tagged with
+// `poller:{name}`.
+#[derive(Debug, Clone)]
+pub struct Statsd {
+    addr: String,
+    prefix: String,
+    tags: Vec<(String, String)>,
+    dogstatsd: bool,
+}
+
+impl Statsd {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            prefix: "json_poller".into(),
+            tags: Vec::new(),
+            dogstatsd: true,
+        }
+    }
+
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    // Sent with every metric, after `poller:{name}`.
+    pub fn tag(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((name.into(), value.into()));
+        self
+    }
+
+    // Plain statsd has no tags: the poller name becomes part of the metric name instead and
+    // `tag`s are not sent.
+    pub fn plain(mut self) -> Self {
+        self.dogstatsd = false;
+        self
+    }
+}
+
+// Fire-and-forget UDP: a missing agent must never slow down or fail a poll.
+pub(crate) struct StatsdEmitter {
+    socket: UdpSocket,
+    prefix: String,
+    tags: String,
+}
+
+impl StatsdEmitter {
+    pub(crate) fn connect(config: Statsd, name: &str) -> io::Result<Self> {
+        let target = config.addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "statsd address did not resolve")
+        })?;
+        let local: SocketAddr = match target {
+            SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+            SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(target)?;
+        socket.set_nonblocking(true)?;
+        let (prefix, tags) = match config.dogstatsd {
+            true => {
+                let tags = std::iter::once(("poller".to_owned(), name.to_owned()))
+                    .chain(config.tags)
+                    .map(|(name, value)| format!("{}:{}", sanitize(&name), sanitize(&value)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                (config.prefix, format!("|#{tags}"))
+            }
+            false => (
+                format!("{}.{}", config.prefix, sanitize(name)),
+                String::new(),
+            ),
+        };
+        Ok(Self {
+            socket,
+            prefix,
+            tags,
+        })
+    }
+
+    fn send(&self, metric: &str, value: impl Display, kind: &str) {
+        let line = format!("{}.{metric}:{value}|{kind}{}", self.prefix, self.tags);
+        let _ = self.socket.send(line.as_bytes());
+    }
+
+    pub(crate) fn record_success(&self, latency: Duration, bytes: u64, wire_bytes: u64) {
+        self.send("fetch.success", 1, "c");
+        self.send("fetch.duration", millis(latency), "ms");
+        self.send("received_bytes", bytes, "c");
+        self.send("received_wire_bytes", wire_bytes, "c");
+    }
+
+    pub(crate) fn record_error(&self) {
+        self.send("fetch.errors", 1, "c");
+    }
+
+    pub(crate) fn record_body_size(&self, bytes: u64) {
+        self.send("payload_bytes", bytes, "h");
+    }
+
+    pub(crate) fn record_parse_time(&self, elapsed: Duration) {
+        self.send("parse_time", millis(elapsed), "ms");
+    }
+
+    pub(crate) fn record_queue_depth(&self, depth: usize) {
+        self.send("queue_depth", depth, "g");
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+// These delimit the line protocol (and spaces confuse some agents).
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            ':' | '|' | ',' | '#' | '@' | ' ' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receive(agent: &UdpSocket) -> String {
+        let mut buf = [0; 512];
+        let len = agent.recv(&mut buf).unwrap();
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+
+    #[test]
+    fn test_lines_are_tagged_or_named_per_poller() {
+        let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        agent
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let addr = agent.local_addr().unwrap().to_string();
+
+        let tagged = Statsd::new(&addr).tag("env", "prod");
+        let emitter = StatsdEmitter::connect(tagged, "prices:eu").unwrap();
+        emitter.record_error();
+        assert_eq!(
+            receive(&agent),
+            "json_poller.fetch.errors:1|c|#poller:prices_eu,env:prod"
+        );
+        emitter.record_success(Duration::from_micros(12_500), 64, 20);
+        assert_eq!(
+            receive(&agent),
+            "json_poller.fetch.success:1|c|#poller:prices_eu,env:prod"
+        );
+        assert_eq!(
+            receive(&agent),
+            "json_poller.fetch.duration:12.500|ms|#poller:prices_eu,env:prod"
+        );
+        assert_eq!(
+            receive(&agent),
+            "json_poller.received_bytes:64|c|#poller:prices_eu,env:prod"
+        );
+        assert_eq!(
+            receive(&agent),
+            "json_poller.received_wire_bytes:20|c|#poller:prices_eu,env:prod"
+        );
+
+        let plain = Statsd::new(&addr).prefix("app").tag("env", "prod").plain();
+        StatsdEmitter::connect(plain, "prices")
+            .unwrap()
+            .record_queue_depth(3);
+        assert_eq!(receive(&agent), "app.prices.queue_depth:3|g");
+    }
+}
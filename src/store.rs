@@ -0,0 +1,182 @@
+use futures_util::future::BoxFuture;
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// What a poller needs to pick up where it left off: the `etag` and change digest (`hash`) of
+// the last handled payload, the resume `cursor` and, for `start_materialized`, the document built
+// so far (`snapshot`). All are saved after handled polls and restored on start.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct PollerState {
+    pub etag: Option<String>,
+    pub cursor: Option<String>,
+    pub hash: Option<u64>,
+    pub snapshot: Option<serde_json::Value>,
+}
+
+// Storage for `PollerState`, keyed by poller name. Implement this to keep state in Redis,
+// Postgres or anywhere else without the crate depending on their clients.
+pub trait StateStore: Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<PollerState>>>;
+
+    fn put<'a>(&'a self, key: &'a str, state: &'a PollerState) -> BoxFuture<'a, io::Result<()>>;
+}
+
+// Lets one store back several pollers, or stay reachable after handing it to a builder.
+impl<S: StateStore + ?Sized> StateStore for Arc<S> {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<PollerState>>> {
+        (**self).get(key)
+    }
+
+    fn put<'a>(&'a self, key: &'a str, state: &'a PollerState) -> BoxFuture<'a, io::Result<()>> {
+        (**self).put(key, state)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    states: Mutex<HashMap<String, PollerState>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StateStore for MemoryStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<PollerState>>> {
+        let state = self.states.lock().unwrap().get(key).cloned();
+        Box::pin(async move { Ok(state) })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, state: &'a PollerState) -> BoxFuture<'a, io::Result<()>> {
+        self.states
+            .lock()
+            .unwrap()
+            .insert(key.to_owned(), state.clone());
+        Box::pin(async { Ok(()) })
+    }
+}
+
+// One `{key}.json` file per poller in `dir`, with every byte of the key other than ASCII letters,
+// digits, `-` and `_` percent-encoded so distinct keys never share a file. Writes go to a
+// temporary file that is renamed into place, so a crash mid-write leaves the previous state
+// intact.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let mut name = String::with_capacity(key.len());
+        for byte in key.bytes() {
+            match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => name.push(byte as char),
+                _ => name.push_str(&format!("%{byte:02X}")),
+            }
+        }
+        self.dir.join(format!("{name}.json"))
+    }
+}
+
+impl StateStore for FileStore {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<PollerState>>> {
+        let path = self.path(key);
+        Box::pin(async move {
+            blocking(move || match std::fs::read(&path) {
+                Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            })
+            .await
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, state: &'a PollerState) -> BoxFuture<'a, io::Result<()>> {
+        let (dir, path) = (self.dir.clone(), self.path(key));
+        let bytes = serde_json::to_vec(state);
+        Box::pin(async move {
+            let bytes = bytes?;
+            blocking(move || {
+                std::fs::create_dir_all(dir)?;
+                let tmp = path.with_extension("json.tmp");
+                std::fs::write(&tmp, bytes)?;
+                std::fs::rename(tmp, path)
+            })
+            .await
+        })
+    }
+}
+
+tokio::task_local! {
+    static IF_NONE_MATCH: Option<HeaderValue>;
+}
+
+// Sends `etag` as `If-None-Match` on the poll's GETs of the primary URL made inside `fetch`, so
+// a payload that was already handled comes back as a 304.
+pub(crate) async fn conditional<F: Future>(etag: Option<HeaderValue>, fetch: F) -> F::Output {
+    IF_NONE_MATCH.scope(etag, fetch).await
+}
+
+pub(crate) fn if_none_match() -> Option<HeaderValue> {
+    IF_NONE_MATCH.try_with(Clone::clone).ok().flatten()
+}
+
+async fn blocking<R: Send + 'static>(
+    f: impl FnOnce() -> io::Result<R> + Send + 'static,
+) -> io::Result<R> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|e| Err(io::Error::other(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn test_file_store_round_trips_state() {
+        let dir = std::env::temp_dir().join(format!("json-poller-store-{}", std::process::id()));
+        let store = FileStore::new(&dir);
+        assert_eq!(store.get("prices/eu").await.unwrap(), None);
+
+        let state = PollerState {
+            etag: Some("\"v1\"".into()),
+            cursor: Some("42".into()),
+            snapshot: Some(serde_json::json!({"a": [1, 2]})),
+            ..PollerState::default()
+        };
+        store.put("prices/eu", &state).await.unwrap();
+        assert_eq!(store.get("prices/eu").await.unwrap(), Some(state));
+        assert!(dir.join("prices%2Feu.json").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_store_keys_do_not_collide() {
+        let store = FileStore::new("state");
+        let paths: Vec<_> = ["a.b", "a-b", "a_b", "a/b", "a%2Fb", "..", "é"]
+            .into_iter()
+            .map(|key| store.path(key))
+            .collect();
+
+        assert_eq!(store.path("a.b"), Path::new("state/a%2Eb.json"));
+        assert_eq!(store.path("é"), Path::new("state/%C3%A9.json"));
+        for (i, path) in paths.iter().enumerate() {
+            assert!(!paths[i + 1..].contains(path), "{path:?}");
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use serde::de::Error as _;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Start,
+    Between,
+    Element,
+    Done,
+}
+
+// Splits a top-level JSON array into its raw elements as chunks arrive, so each element can be
+// deserialized without buffering the whole body. Only element boundaries are tracked here;
+// validating the elements themselves is left to serde_json.
+#[derive(Debug, Default)]
+pub(crate) struct ArraySplitter {
+    state: State,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    current: Vec<u8>,
+}
+
+type Emit<'a> = dyn FnMut(&[u8]) -> Result<(), serde_json::Error> + 'a;
+
+impl ArraySplitter {
+    pub(crate) fn feed(
+        &mut self,
+        chunk: &[u8],
+        emit: &mut Emit<'_>,
+    ) -> Result<(), serde_json::Error> {
+        for &byte in chunk {
+            match self.state {
+                State::Start if byte == b'[' => self.state = State::Between,
+                State::Start if byte.is_ascii_whitespace() => {}
+                State::Start => {
+                    return Err(serde_json::Error::custom("expected a top-level JSON array"))
+                }
+                State::Between if byte == b']' => self.state = State::Done,
+                State::Between if byte == b',' || byte.is_ascii_whitespace() => {}
+                State::Between | State::Element => {
+                    self.state = State::Element;
+                    self.scan(byte, emit)?;
+                }
+                State::Done if byte.is_ascii_whitespace() => {}
+                State::Done => {
+                    return Err(serde_json::Error::custom(
+                        "trailing characters after JSON array",
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), serde_json::Error> {
+        match self.state {
+            State::Done => Ok(()),
+            _ => Err(serde_json::Error::custom("unexpected end of JSON array")),
+        }
+    }
+
+    fn scan(&mut self, byte: u8, emit: &mut Emit<'_>) -> Result<(), serde_json::Error> {
+        if self.in_string {
+            self.current.push(byte);
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+            }
+            return Ok(());
+        }
+        match byte {
+            b'"' => {
+                self.in_string = true;
+                self.current.push(byte);
+            }
+            b'{' | b'[' => {
+                self.depth += 1;
+                self.current.push(byte);
+            }
+            b'}' | b']' if self.depth > 0 => {
+                self.depth -= 1;
+                self.current.push(byte);
+                if self.depth == 0 {
+                    self.emit(State::Between, emit)?;
+                }
+            }
+            // A scalar element runs until the next separator or the end of the array.
+            b']' => self.emit(State::Done, emit)?,
+            b',' if self.depth == 0 => self.emit(State::Between, emit)?,
+            _ => self.current.push(byte),
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, next: State, emit: &mut Emit<'_>) -> Result<(), serde_json::Error> {
+        self.state = next;
+        let result = emit(&self.current);
+        self.current.clear();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(chunks: &[&str]) -> Result<Vec<String>, serde_json::Error> {
+        let mut splitter = ArraySplitter::default();
+        let mut items = Vec::new();
+        for chunk in chunks {
+            splitter.feed(chunk.as_bytes(), &mut |raw| {
+                items.push(String::from_utf8_lossy(raw).trim().to_string());
+                Ok(())
+            })?;
+        }
+        splitter.finish()?;
+        Ok(items)
+    }
+
+    #[test]
+    fn test_elements_split_across_chunks() {
+        let items = split(&[" [{\"a\": [1, ", "2]}, \"x,]\\\"\"", ", 3 ,null", "] "]).unwrap();
+
+        assert_eq!(items, ["{\"a\": [1, 2]}", "\"x,]\\\"\"", "3", "null"]);
+        assert!(split(&["[]"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_arrays_are_rejected() {
+        assert!(split(&["{\"a\": 1}"]).is_err());
+        assert!(split(&["[1, 2"]).is_err());
+        assert!(split(&["[1] 2"]).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer};
+use serde_json::{Error, Value};
+use std::sync::Mutex;
+
+// What `unknown_fields` does with payload fields that `T` does not declare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownFields {
+    // Log them and emit `PollEvent::UnknownFields` whenever the set changes.
+    Warn,
+    // Fail the poll with `JsonPollerError::Decode`.
+    Deny,
+}
+
+// Deserializes from a parsed document and records the JSON pointer of every value the target
+// type skipped, which serde routes through `deserialize_ignored_any`.
+pub(crate) struct Tracked<'a> {
+    value: Value,
+    path: String,
+    unknown: &'a mut Vec<String>,
+}
+
+impl<'a> Tracked<'a> {
+    pub(crate) fn new(value: Value, unknown: &'a mut Vec<String>) -> Self {
+        Self {
+            value,
+            path: String::new(),
+            unknown,
+        }
+    }
+}
+
+fn child(path: &str, key: &str) -> String {
+    format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+impl<'de> Deserializer<'de> for Tracked<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Number(n) => n.deserialize_any(visitor),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Array(items) => {
+                let len = items.len();
+                let mut seq = Seq {
+                    items: items.into_iter().enumerate(),
+                    path: self.path,
+                    unknown: self.unknown,
+                };
+                let value = visitor.visit_seq(&mut seq)?;
+                match seq.items.len() {
+                    0 => Ok(value),
+                    _ => Err(de::Error::invalid_length(len, &"fewer elements in array")),
+                }
+            }
+            Value::Object(map) => visitor.visit_map(Map {
+                entries: map.into_iter(),
+                pending: None,
+                path: self.path,
+                unknown: self.unknown,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("one entry");
+                visitor.visit_enum(Enum {
+                    path: child(&self.path, &variant),
+                    variant,
+                    value,
+                    unknown: self.unknown,
+                })
+            }
+            _ => Err(de::Error::custom(
+                "expected a string or a single-key object",
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.unknown.push(self.path);
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+struct Seq<'a> {
+    items: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    path: String,
+    unknown: &'a mut Vec<String>,
+}
+
+impl<'de> SeqAccess<'de> for Seq<'_> {
+    type Error = Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Error> {
+        let Some((index, value)) = self.items.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(Tracked {
+            value,
+            path: child(&self.path, &index.to_string()),
+            unknown: self.unknown,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct Map<'a> {
+    entries: serde_json::map::IntoIter,
+    pending: Option<(String, Value)>,
+    path: String,
+    unknown: &'a mut Vec<String>,
+}
+
+impl<'de> MapAccess<'de> for Map<'_> {
+    type Error = Error;
+
+    fn next_key_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let path = child(&self.path, &key);
+        self.pending = Some((path, value));
+        seed.deserialize(Key(key)).map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, Error> {
+        let (path, value) = self
+            .pending
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before its key"))?;
+        seed.deserialize(Tracked {
+            value,
+            path,
+            unknown: self.unknown,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct Enum<'a> {
+    variant: String,
+    value: Value,
+    path: String,
+    unknown: &'a mut Vec<String>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for Enum<'a> {
+    type Error = Error;
+    type Variant = Tracked<'a>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Tracked<'a>), Error> {
+        let variant = seed.deserialize(Key(self.variant))?;
+        let content = Tracked {
+            value: self.value,
+            path: self.path,
+            unknown: self.unknown,
+        };
+        Ok((variant, content))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Tracked<'_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            Value::Null => Ok(()),
+            _ => Err(de::Error::custom("expected a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+}
+
+// Object keys, which also stand in for numbers when `T` is a map keyed by integers.
+struct Key(String);
+
+macro_rules! parse_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.0.parse() {
+                Ok(n) => visitor.$visit(n),
+                Err(_) => visitor.visit_string(self.0),
+            }
+        })*
+    };
+}
+
+impl<'de> Deserializer<'de> for Key {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_key! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+// Reports each distinct set of unknown fields once, so a drifted API doesn't warn on every poll.
+#[derive(Default)]
+pub(crate) struct FieldWatch {
+    reported: Mutex<Vec<String>>,
+}
+
+impl FieldWatch {
+    pub(crate) fn changed(&self, fields: &[String]) -> bool {
+        let mut reported = self.reported.lock().unwrap();
+        if *reported == fields {
+            return false;
+        }
+        *reported = fields.to_vec();
+        !fields.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Quote {
+        symbol: String,
+        levels: Vec<Level>,
+        by_id: HashMap<u32, Kind>,
+        note: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Level {
+        price: f64,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Kind {
+        Bid,
+        Ask { size: u32 },
+    }
+
+    #[test]
+    fn test_skipped_fields_are_reported_by_pointer() {
+        let value = json!({
+            "symbol": "EURUSD",
+            "venue": "x",
+            "levels": [{"price": 1.0}, {"price": 1.1, "size": 3}],
+            "by_id": {"7": "bid", "8": {"ask": {"size": 2, "flags": 0}}},
+            "note": null,
+            "a/b": 1
+        });
+        let mut unknown = Vec::new();
+        let quote = Quote::deserialize(Tracked::new(value, &mut unknown)).unwrap();
+        assert_eq!(quote.levels[1], Level { price: 1.1 });
+        assert_eq!(quote.by_id[&8], Kind::Ask { size: 2 });
+        unknown.sort();
+        assert_eq!(
+            unknown,
+            ["/a~1b", "/by_id/8/ask/flags", "/levels/1/size", "/venue"]
+        );
+    }
+
+    #[test]
+    fn test_each_set_is_reported_once() {
+        let watch = FieldWatch::default();
+        assert!(!watch.changed(&[]));
+        assert!(watch.changed(&["/venue".into()]));
+        assert!(!watch.changed(&["/venue".into()]));
+        assert!(!watch.changed(&[]));
+        assert!(watch.changed(&["/venue".into()]));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::StartError;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::{JoinError, JoinHandle};
+
+pub struct PollerTask<E> {
+    handle: JoinHandle<Result<(), StartError<E>>>,
+    shutdown: watch::Sender<bool>,
+    abort_on_drop: bool,
+    drain_on_drop: Option<Duration>,
+}
+
+impl<E> PollerTask<E> {
+    pub(crate) fn new(
+        handle: JoinHandle<Result<(), StartError<E>>>,
+        shutdown: watch::Sender<bool>,
+    ) -> Self {
+        Self {
+            handle,
+            shutdown,
+            abort_on_drop: false,
+            drain_on_drop: None,
+        }
+    }
+
+    pub fn abort_on_drop(mut self) -> Self {
+        self.abort_on_drop = true;
+        self
+    }
+
+    pub fn drain_on_drop(mut self, timeout: Duration) -> Self {
+        self.drain_on_drop = Some(timeout);
+        self
+    }
+
+    pub fn abort(&self) {
+        self.handle.abort();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    // Lets the current request and callback finish, then stops before the next tick. The task
+    // is aborted if that takes longer than `timeout`.
+    pub async fn shutdown(
+        mut self,
+        timeout: Duration,
+    ) -> Result<Result<(), StartError<E>>, JoinError> {
+        self.drain_on_drop = None;
+        self.shutdown.send_replace(true);
+        match tokio::time::timeout(timeout, &mut self.handle).await {
+            Ok(result) => result,
+            Err(_) => {
+                self.handle.abort();
+                (&mut self.handle).await
+            }
+        }
+    }
+}
+
+impl<E> Future for PollerTask<E> {
+    type Output = Result<Result<(), StartError<E>>, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle).poll(cx)
+    }
+}
+
+impl<E> Drop for PollerTask<E> {
+    fn drop(&mut self) {
+        if let Some(timeout) = self.drain_on_drop {
+            self.shutdown.send_replace(true);
+            let abort = self.handle.abort_handle();
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    runtime.spawn(async move {
+                        tokio::time::sleep(timeout).await;
+                        abort.abort();
+                    });
+                }
+                Err(_) => abort.abort(),
+            }
+        } else if self.abort_on_drop {
+            self.handle.abort();
+        }
+    }
+}
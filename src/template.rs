@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Values for `{name}` placeholders in header and query values, supplied per request by the
+// `template_context` provider. `{epoch}` (Unix seconds) is filled in unless the context sets it.
+pub type TemplateContext = HashMap<String, String>;
+
+pub(crate) type ContextProvider = Arc<dyn Fn() -> TemplateContext + Send + Sync>;
+
+// The headers and query parameters that contain placeholders, rendered again for every request.
+pub(crate) struct Templates {
+    context: ContextProvider,
+    headers: Vec<(String, String)>,
+    query: Vec<(String, String)>,
+}
+
+pub(crate) struct Rendered {
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) query: Vec<(String, String)>,
+}
+
+pub(crate) fn is_template(value: &str) -> bool {
+    value.contains(['{', '}'])
+}
+
+// Checks the placeholder syntax without a context.
+pub(crate) fn check(value: &str) -> Result<(), String> {
+    render_with(value, |_| Some(String::new())).map(drop)
+}
+
+impl Templates {
+    pub(crate) fn new(
+        context: ContextProvider,
+        headers: Vec<(String, String)>,
+        query: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            context,
+            headers,
+            query,
+        }
+    }
+
+    pub(crate) fn render(&self) -> Result<Rendered, String> {
+        let context = (self.context)();
+        let lookup = |name: &str| match context.get(name) {
+            Some(value) => Some(value.clone()),
+            None if name == "epoch" => Some(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs())
+                    .to_string(),
+            ),
+            None => None,
+        };
+        let render_all = |pairs: &[(String, String)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), render_with(value, lookup)?)))
+                .collect::<Result<Vec<_>, String>>()
+        };
+        Ok(Rendered {
+            headers: render_all(&self.headers)?,
+            query: render_all(&self.query)?,
+        })
+    }
+}
+
+// `{{` and `}}` stand for literal braces.
+fn render_with(template: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(at) = rest.find(['{', '}']) {
+        out.push_str(&rest[..at]);
+        let brace = &rest[at..=at];
+        let after = &rest[at + 1..];
+        if let Some(after) = after.strip_prefix(brace) {
+            out.push_str(brace);
+            rest = after;
+            continue;
+        }
+        if brace == "}" {
+            return Err(format!("unmatched `}}` in `{template}`"));
+        }
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("unclosed `{{` in `{template}`"))?;
+        let name = &after[..end];
+        let value = lookup(name).ok_or_else(|| format!("no template variable `{name}`"))?;
+        out.push_str(&value);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_are_resolved_from_the_context() {
+        let templates = Templates::new(
+            Arc::new(|| TemplateContext::from([("tenant".into(), "acme".into())])),
+            vec![("X-Tenant".into(), "{tenant}".into())],
+            vec![
+                ("since".into(), "{epoch}".into()),
+                ("filter".into(), "{{\"t\":\"{tenant}\"}}".into()),
+            ],
+        );
+        let rendered = templates.render().unwrap();
+        assert_eq!(rendered.headers, [("X-Tenant".into(), "acme".into())]);
+        assert!(rendered.query[0].1.parse::<u64>().unwrap() > 1_600_000_000);
+        assert_eq!(rendered.query[1].1, r#"{"t":"acme"}"#);
+
+        let missing = Templates::new(
+            Arc::new(TemplateContext::new),
+            vec![("X-Region".into(), "{region}".into())],
+            Vec::new(),
+        );
+        assert_eq!(
+            missing.render().err().unwrap(),
+            "no template variable `region`"
+        );
+        assert!(check("{tenant").is_err());
+        assert!(check("a}b").is_err());
+    }
+}
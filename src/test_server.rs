@@ -0,0 +1,149 @@
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+pub struct TestResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Option<Duration>,
+    pub reset: bool,
+}
+
+impl TestResponse {
+    pub fn json(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.into().into_bytes(),
+            delay: None,
+            reset: false,
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            delay: None,
+            reset: false,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    // Aborts the connection with a RST instead of answering.
+    pub fn reset() -> Self {
+        Self {
+            reset: true,
+            ..Self::status(200)
+        }
+    }
+
+    pub fn delay_ms(mut self, ms: u64) -> Self {
+        self.delay = Some(Duration::from_millis(ms));
+        self
+    }
+}
+
+pub struct TestServer {
+    pub url: String,
+    hits: Arc<AtomicUsize>,
+    requests: Arc<Mutex<Vec<String>>>,
+    peers: Arc<Mutex<Vec<SocketAddr>>>,
+}
+
+impl TestServer {
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn peers(&self) -> Vec<SocketAddr> {
+        self.peers.lock().unwrap().clone()
+    }
+}
+
+pub async fn serve<F>(handler: F) -> TestServer
+where
+    F: Fn(usize, &str) -> TestResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let handler = Arc::new(handler);
+
+    let server_hits = hits.clone();
+    let server_requests = requests.clone();
+    let server_peers = peers.clone();
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, peer)) = listener.accept().await else {
+                return;
+            };
+            server_peers.lock().unwrap().push(peer);
+            let hits = server_hits.clone();
+            let requests = server_requests.clone();
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&head).into_owned();
+                let index = hits.fetch_add(1, Ordering::SeqCst);
+                requests.lock().unwrap().push(head.clone());
+
+                let response = handler(index, &head);
+                if let Some(delay) = response.delay {
+                    tokio::time::sleep(delay).await;
+                }
+                if response.reset {
+                    #[allow(deprecated)]
+                    let _ = socket.set_linger(Some(Duration::ZERO));
+                    return;
+                }
+
+                let mut out = format!("HTTP/1.1 {} Test\r\n", response.status);
+                for (name, value) in &response.headers {
+                    out.push_str(&format!("{name}: {value}\r\n"));
+                }
+                out.push_str(&format!(
+                    "Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    response.body.len()
+                ));
+                let mut bytes = out.into_bytes();
+                if !head.starts_with("HEAD ") {
+                    bytes.extend_from_slice(&response.body);
+                }
+                let _ = socket.write_all(&bytes).await;
+                let _ = socket.shutdown().await;
+            });
+        }
+    });
+
+    TestServer {
+        url,
+        hits,
+        requests,
+        peers,
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{base64, IntegrityError};
+use reqwest::header::HeaderMap;
+use ring::{digest, hmac, signature};
+
+// Public key (or shared secret) for `verify_signature`.
+#[derive(Debug, Clone)]
+pub enum SignatureKey {
+    // Raw 32-byte public key.
+    Ed25519(Vec<u8>),
+    // Uncompressed SEC1 point; header signatures are ASN.1 DER encoded.
+    EcdsaP256Sha256(Vec<u8>),
+    // DER `RSAPublicKey`.
+    RsaPkcs1Sha256(Vec<u8>),
+    HmacSha256(Vec<u8>),
+}
+
+impl SignatureKey {
+    // The JWS `alg` this key verifies.
+    pub(crate) fn jws_alg(&self) -> &'static str {
+        match self {
+            Self::Ed25519(_) => "EdDSA",
+            Self::EcdsaP256Sha256(_) => "ES256",
+            Self::RsaPkcs1Sha256(_) => "RS256",
+            Self::HmacSha256(_) => "HS256",
+        }
+    }
+
+    pub(crate) fn verify(&self, message: &[u8], sig: &[u8]) -> bool {
+        self.verify_with(message, sig, &signature::ECDSA_P256_SHA256_ASN1)
+    }
+
+    // JWS encodes ECDSA signatures as fixed-length `r || s` instead of ASN.1.
+    pub(crate) fn verify_jws(&self, message: &[u8], sig: &[u8]) -> bool {
+        self.verify_with(message, sig, &signature::ECDSA_P256_SHA256_FIXED)
+    }
+
+    fn verify_with(
+        &self,
+        message: &[u8],
+        sig: &[u8],
+        ecdsa: &'static signature::EcdsaVerificationAlgorithm,
+    ) -> bool {
+        let public = |algorithm: &'static dyn signature::VerificationAlgorithm, key: &[u8]| {
+            signature::UnparsedPublicKey::new(algorithm, key)
+                .verify(message, sig)
+                .is_ok()
+        };
+        match self {
+            Self::Ed25519(key) => public(&signature::ED25519, key),
+            Self::EcdsaP256Sha256(key) => public(ecdsa, key),
+            Self::RsaPkcs1Sha256(key) => public(&signature::RSA_PKCS1_2048_8192_SHA256, key),
+            Self::HmacSha256(secret) => {
+                hmac::verify(&hmac::Key::new(hmac::HMAC_SHA256, secret), message, sig).is_ok()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Verifier {
+    Digest,
+    Signature { header: String, key: SignatureKey },
+}
+
+impl Verifier {
+    pub(crate) fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), IntegrityError> {
+        match self {
+            Self::Digest => verify_digest(headers, body),
+            Self::Signature { header, key } => {
+                let value = header_value(headers, header)?;
+                let sig = decode_signature(value)
+                    .ok_or_else(|| IntegrityError::Malformed(header.clone()))?;
+                match key.verify(body, &sig) {
+                    true => Ok(()),
+                    false => Err(IntegrityError::Mismatch(header.clone())),
+                }
+            }
+        }
+    }
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, IntegrityError> {
+    let value = headers
+        .get(name)
+        .ok_or_else(|| IntegrityError::Missing(name.to_owned()))?;
+    value
+        .to_str()
+        .map_err(|_| IntegrityError::Malformed(name.to_owned()))
+}
+
+// `Content-Digest: sha-256=:<base64>:` (RFC 9530) or the older `Digest: SHA-256=<base64>`
+// (RFC 3230). The strongest supported algorithm listed is checked.
+fn verify_digest(headers: &HeaderMap, body: &[u8]) -> Result<(), IntegrityError> {
+    let (name, value) = ["content-digest", "digest"]
+        .into_iter()
+        .find_map(|name| Some((name, headers.get(name)?)))
+        .ok_or_else(|| IntegrityError::Missing("Content-Digest".into()))?;
+    let malformed = || IntegrityError::Malformed(name.to_owned());
+    let value = value.to_str().map_err(|_| malformed())?;
+    let mut best = None;
+    for entry in value.split(',') {
+        let Some((algorithm, encoded)) = entry.trim().split_once('=') else {
+            continue;
+        };
+        let algorithm = match algorithm.to_ascii_lowercase().as_str() {
+            "sha-512" => &digest::SHA512,
+            "sha-256" => &digest::SHA256,
+            _ => continue,
+        };
+        if best.is_none_or(|(current, _): (&digest::Algorithm, _)| {
+            current.output_len() < algorithm.output_len()
+        }) {
+            best = Some((algorithm, encoded.trim().trim_matches(':')));
+        }
+    }
+    let (algorithm, encoded) = best.ok_or_else(malformed)?;
+    let expected = base64::decode(encoded).ok_or_else(malformed)?;
+    match digest::digest(algorithm, body).as_ref() == expected {
+        true => Ok(()),
+        false => Err(IntegrityError::Mismatch(name.to_owned())),
+    }
+}
+
+// Base64 (standard or URL-safe alphabet, padding optional), or GitHub-style `sha256=<hex>`.
+fn decode_signature(value: &str) -> Option<Vec<u8>> {
+    match value.trim().strip_prefix("sha256=") {
+        Some(hex) => hex_decode(hex),
+        None => base64::decode(value.trim()),
+    }
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    #[test]
+    fn test_digest_header_must_match_body() {
+        let body = br#"{"hello":"world"}"#;
+        let sha256 = base64::encode(digest::digest(&digest::SHA256, body).as_ref());
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            verify_digest(&headers, body),
+            Err(IntegrityError::Missing("Content-Digest".into()))
+        );
+
+        headers.insert(
+            "digest",
+            format!("MD5=abc, SHA-256={sha256}").parse().unwrap(),
+        );
+        assert_eq!(verify_digest(&headers, body), Ok(()));
+        assert_eq!(
+            verify_digest(&headers, b"{}"),
+            Err(IntegrityError::Mismatch("digest".into()))
+        );
+
+        headers.insert(
+            "content-digest",
+            format!("sha-256=:{sha256}:").parse().unwrap(),
+        );
+        assert_eq!(verify_digest(&headers, body), Ok(()));
+    }
+
+    #[test]
+    fn test_ed25519_signature_header() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let verifier = Verifier::Signature {
+            header: "x-content-signature".into(),
+            key: SignatureKey::Ed25519(pair.public_key().as_ref().to_vec()),
+        };
+        let body = br#"{"amount":10}"#;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-content-signature",
+            base64::encode(pair.sign(body).as_ref()).parse().unwrap(),
+        );
+
+        assert_eq!(verifier.verify(&headers, body), Ok(()));
+        assert_eq!(
+            verifier.verify(&headers, br#"{"amount":99}"#),
+            Err(IntegrityError::Mismatch("x-content-signature".into()))
+        );
+    }
+
+    #[test]
+    fn test_hmac_signature_in_github_style() {
+        let key = SignatureKey::HmacSha256(b"secret".to_vec());
+        let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, b"secret"), b"{}");
+        let hex: String = tag.as_ref().iter().map(|b| format!("{b:02x}")).collect();
+        assert!(key.verify(b"{}", &decode_signature(&format!("sha256={hex}")).unwrap()));
+        assert!(!key.verify(b"[]", &decode_signature(&format!("sha256={hex}")).unwrap()));
+    }
+}
@@ -0,0 +1,287 @@
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    UnixSeconds,
+    UnixMillis,
+    // `2024-05-01T12:00:00Z`, with optional fractional seconds and a numeric offset.
+    Rfc3339,
+}
+
+impl TimestampFormat {
+    fn parse(self, value: &Value) -> Option<SystemTime> {
+        let number = || match value {
+            Value::String(s) => s.parse::<f64>().ok(),
+            _ => value.as_f64(),
+        };
+        let since_epoch = match self {
+            Self::UnixSeconds => Duration::try_from_secs_f64(number()?).ok()?,
+            Self::UnixMillis => Duration::try_from_secs_f64(number()? / 1000.0).ok()?,
+            Self::Rfc3339 => return parse_rfc3339(value.as_str()?),
+        };
+        UNIX_EPOCH.checked_add(since_epoch)
+    }
+}
+
+// Server-side timestamp read from each payload. The data age is the receive time (shifted by
+// the `Date` header's clock skew when known) minus that timestamp; an upstream that keeps
+// answering with old data shows up here even though every request succeeds.
+pub(crate) struct Watermark<T> {
+    pointer: String,
+    format: TimestampFormat,
+    stale_after: Option<Duration>,
+    to_value: fn(&T) -> Option<Value>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    age: Option<Duration>,
+    stale: bool,
+}
+
+impl<T> Watermark<T> {
+    pub(crate) fn new(pointer: String, format: TimestampFormat) -> Self
+    where
+        T: serde::Serialize,
+    {
+        Self {
+            pointer,
+            format,
+            stale_after: None,
+            to_value: |data| serde_json::to_value(data).ok(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub(crate) fn stale_after(&mut self, age: Duration) {
+        self.stale_after = Some(age);
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self.pointer.starts_with('/') {
+            true => Ok(()),
+            false => Err(format!("`{}` is not a JSON pointer", self.pointer)),
+        }
+    }
+
+    // Returns the data age when it has just crossed `stale_after`.
+    pub(crate) fn observe(&self, received: SystemTime, data: &T) -> Option<Duration> {
+        let stamped = (self.to_value)(data)
+            .and_then(|value| self.format.parse(value.pointer(&self.pointer)?));
+        let age = stamped.map(|at| received.duration_since(at).unwrap_or_default());
+        let mut state = self.state.lock().unwrap();
+        state.age = age;
+        let stale = match (age, self.stale_after) {
+            (Some(age), Some(limit)) => age > limit,
+            _ => false,
+        };
+        let became_stale = stale && !state.stale;
+        state.stale = stale;
+        became_stale.then_some(age?)
+    }
+
+    pub(crate) fn age(&self) -> Option<Duration> {
+        self.state.lock().unwrap().age
+    }
+}
+
+pub(crate) fn parse_rfc3339(s: &str) -> Option<SystemTime> {
+    let digits = |range: std::ops::Range<usize>| -> Option<u32> {
+        let part = s.get(range)?;
+        part.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| part.parse().ok())?
+    };
+    let (year, month, day) = (digits(0..4)?, digits(5..7)?, digits(8..10)?);
+    let (hour, minute, second) = (digits(11..13)?, digits(14..16)?, digits(17..19)?);
+    let separators = s.as_bytes();
+    if separators.get(4) != Some(&b'-')
+        || separators.get(7) != Some(&b'-')
+        || !matches!(separators.get(10), Some(b'T' | b't' | b' '))
+        || separators.get(13) != Some(&b':')
+        || separators.get(16) != Some(&b':')
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let mut rest = &s[19..];
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        let padded = format!("{:0<9}", &fraction[..len.min(9)]);
+        nanos = padded.parse().ok()?;
+        rest = &fraction[len..];
+    }
+    let offset: i64 = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let (h, m) = rest[1..].split_once(':')?;
+            if h.len() != 2 || m.len() != 2 {
+                return None;
+            }
+            sign * (h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().ok()? * 60)
+        }
+    };
+
+    let secs = days_from_civil(year as i64, month, day) * 86_400
+        + i64::from(hour * 3600 + minute * 60 + second)
+        - offset;
+    let secs = u64::try_from(secs).ok()?;
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+}
+
+// UTC with millisecond precision: `2024-05-01T12:00:00.250Z`.
+pub(crate) fn format_rfc3339(at: SystemTime) -> String {
+    let since = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since.as_secs() as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let second_of_day = secs.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60,
+        since.subsec_millis()
+    )
+}
+
+// `Date` header format (IMF-fixdate): `Sun, 06 Nov 1994 08:49:37 GMT`.
+pub(crate) fn parse_http_date(s: &str) -> Option<SystemTime> {
+    let mut parts = s.split_whitespace();
+    let (_weekday, day, month, year, time, zone) = (
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+    );
+    if zone != "GMT" || parts.next().is_some() {
+        return None;
+    }
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    let rfc3339 = format!("{year:0>4}-{month:02}-{day:0>2}T{time}Z");
+    parse_rfc3339(&rfc3339)
+}
+
+// Server clock minus local clock in milliseconds, from the response's `Date` header. The header
+// has one-second resolution, so this is only an estimate.
+pub(crate) fn clock_skew_ms(date: &str, received: SystemTime) -> Option<i64> {
+    let server = parse_http_date(date)?;
+    let millis = |d: Duration| i64::try_from(d.as_millis()).unwrap_or(i64::MAX);
+    Some(match server.duration_since(received) {
+        Ok(ahead) => millis(ahead),
+        Err(behind) => -millis(behind.duration()),
+    })
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// The inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_timestamp_formats() {
+        let rfc = TimestampFormat::Rfc3339;
+        assert_eq!(rfc.parse(&json!("1970-01-01T00:00:00Z")), Some(at(0)));
+        assert_eq!(
+            rfc.parse(&json!("2024-05-01T12:30:00.250+02:00")),
+            Some(at(1_714_559_400) + Duration::from_millis(250))
+        );
+        assert_eq!(
+            format_rfc3339(at(1_714_559_400) + Duration::from_millis(250)),
+            "2024-05-01T10:30:00.250Z"
+        );
+        assert_eq!(
+            format_rfc3339(at(1_709_164_800)),
+            "2024-02-29T00:00:00.000Z"
+        );
+        assert_eq!(rfc.parse(&json!("2024-13-01T00:00:00Z")), None);
+        assert_eq!(rfc.parse(&json!("yesterday")), None);
+        assert_eq!(
+            TimestampFormat::UnixSeconds.parse(&json!(1_714_559_400)),
+            Some(at(1_714_559_400))
+        );
+        assert_eq!(
+            TimestampFormat::UnixMillis.parse(&json!("1714559400000")),
+            Some(at(1_714_559_400))
+        );
+    }
+
+    #[test]
+    fn test_clock_skew_from_date_header() {
+        let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+        assert_eq!(parse_http_date(date), Some(at(784_111_777)));
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(clock_skew_ms(date, at(784_111_770)), Some(7_000));
+        assert_eq!(clock_skew_ms(date, at(784_111_787)), Some(-10_000));
+    }
+
+    #[test]
+    fn test_stale_data_is_reported_once() {
+        let mut watermark =
+            Watermark::<Value>::new("/updated".into(), TimestampFormat::UnixSeconds);
+        watermark.stale_after(Duration::from_secs(60));
+
+        assert_eq!(watermark.observe(at(130), &json!({"updated": 100})), None);
+        assert_eq!(watermark.age(), Some(Duration::from_secs(30)));
+        assert_eq!(
+            watermark.observe(at(200), &json!({"updated": 100})),
+            Some(Duration::from_secs(100))
+        );
+        assert_eq!(watermark.observe(at(210), &json!({"updated": 100})), None);
+        assert_eq!(watermark.observe(at(220), &json!({"updated": 200})), None);
+        assert_eq!(watermark.observe(at(230), &json!({})), None);
+        assert_eq!(watermark.age(), None);
+    }
+}
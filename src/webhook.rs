@@ -0,0 +1,245 @@
+use crate::sink::PollSink;
+use crate::{patch, PollMeta};
+use reqwest::StatusCode;
+use ring::hmac;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WebhookError {
+    Serialize(serde_json::Error),
+    Http(reqwest::Error),
+    Status(StatusCode),
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize(e) => write!(f, "failed to serialize webhook payload: {e}"),
+            Self::Http(e) => write!(f, "webhook request failed: {e}"),
+            Self::Status(status) => write!(f, "webhook answered HTTP {status}"),
+        }
+    }
+}
+
+impl WebhookError {
+    // Transport failures, 429 and 5xx may go away on a second try; other statuses won't.
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Serialize(_) => false,
+            Self::Http(_) => true,
+            Self::Status(status) => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+}
+
+impl std::error::Error for WebhookError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialize(e) => Some(e),
+            Self::Http(e) => Some(e),
+            Self::Status(_) => None,
+        }
+    }
+}
+
+// POSTs every changed payload to a webhook. With a secret, the body is signed with HMAC-SHA256
+// in the GitHub style (`X-Hub-Signature-256: sha256=<hex>`). A delivery that still fails after
+// the retries is returned as the sink error, which stops the poller. Each request times out
+// after `timeout` (10 seconds by default), since the poll loop waits for the delivery.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+    key: Option<hmac::Key>,
+    signature_header: String,
+    timeout: Duration,
+    retries: u32,
+    retry_delay: Duration,
+    diffs: bool,
+    last: Option<Value>,
+}
+
+impl WebhookSink {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            key: None,
+            signature_header: "X-Hub-Signature-256".into(),
+            timeout: Duration::from_secs(10),
+            retries: 3,
+            retry_delay: Duration::from_millis(500),
+            diffs: false,
+            last: None,
+        }
+    }
+
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn hmac_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.key = Some(hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref()));
+        self
+    }
+
+    pub fn signature_header(mut self, header: impl Into<String>) -> Self {
+        self.signature_header = header.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    // Send an RFC 6902 patch against the previous payload instead of the payload itself. The
+    // first delivery's patch replaces the whole document.
+    pub fn diffs(mut self, diffs: bool) -> Self {
+        self.diffs = diffs;
+        self
+    }
+
+    fn body(&self, current: &Value) -> Option<Value> {
+        match &self.last {
+            Some(last) if last == current => None,
+            Some(last) if self.diffs => Some(Value::Array(patch::diff(last, current))),
+            None if self.diffs => Some(Value::Array(patch::diff(&Value::Null, current))),
+            _ => Some(current.clone()),
+        }
+    }
+
+    async fn post(&self, body: &[u8]) -> Result<(), WebhookError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(key) = &self.key {
+            request = request.header(&self.signature_header, signature(key, body));
+        }
+        let response = request.send().await.map_err(WebhookError::Http)?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(WebhookError::Status(status)),
+        }
+    }
+}
+
+impl<T: Serialize + Send> PollSink<T> for WebhookSink {
+    type Error = WebhookError;
+
+    async fn deliver(&mut self, item: T, _meta: PollMeta) -> Result<(), Self::Error> {
+        let current = serde_json::to_value(&item).map_err(WebhookError::Serialize)?;
+        let Some(body) = self.body(&current) else {
+            return Ok(());
+        };
+        let body = serde_json::to_vec(&body).map_err(WebhookError::Serialize)?;
+
+        let mut attempt = 0;
+        loop {
+            match self.post(&body).await {
+                Ok(()) => break,
+                Err(e) if attempt < self.retries && e.is_retryable() => {
+                    attempt += 1;
+                    tracing::debug!("Webhook delivery failed, retrying: {}", e);
+                    tokio::time::sleep(self.retry_delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        self.last = Some(current);
+        Ok(())
+    }
+}
+
+fn signature(key: &hmac::Key, body: &[u8]) -> String {
+    let tag = hmac::sign(key, body);
+    let hex: String = tag
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{serve, TestResponse};
+
+    #[test]
+    fn test_signature_matches_rfc_4231() {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"Jefe");
+        assert_eq!(
+            signature(&key, b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changed_payloads_are_signed_and_retried() {
+        let server = serve(|index, _| match index {
+            0 => TestResponse::status(500),
+            _ => TestResponse::status(204),
+        })
+        .await;
+        let mut sink = WebhookSink::new(&server.url)
+            .hmac_secret("secret")
+            .retry_delay(Duration::from_millis(1));
+
+        let value = serde_json::json!({"a": 1});
+        sink.deliver(value.clone(), PollMeta::new(0)).await.unwrap();
+        sink.deliver(value, PollMeta::new(0)).await.unwrap();
+        sink.deliver(serde_json::json!({"a": 2}), PollMeta::new(0))
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|r| r.starts_with("POST ") && r.contains("x-hub-signature-256: sha256=")));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_and_unanswered_deliveries_fail_without_hanging() {
+        let rejecting = serve(|_, _| TestResponse::status(400)).await;
+        let mut sink = WebhookSink::new(&rejecting.url).retry_delay(Duration::from_millis(1));
+        let result = sink.deliver(1, PollMeta::new(0)).await;
+        assert!(matches!(
+            result,
+            Err(WebhookError::Status(StatusCode::BAD_REQUEST))
+        ));
+        assert_eq!(rejecting.hits(), 1);
+
+        let silent = serve(|_, _| TestResponse::status(204).delay_ms(5_000)).await;
+        let mut sink = WebhookSink::new(&silent.url)
+            .timeout(Duration::from_millis(20))
+            .retries(1)
+            .retry_delay(Duration::from_millis(1));
+        let result =
+            tokio::time::timeout(Duration::from_secs(1), sink.deliver(1, PollMeta::new(0)))
+                .await
+                .unwrap();
+        assert!(matches!(result, Err(WebhookError::Http(e)) if e.is_timeout()));
+        assert_eq!(silent.hits(), 2);
+    }
+}